@@ -24,16 +24,58 @@ pub enum Commands {
         patches: Option<PathBuf>,
         #[arg(long)]
         flags: Vec<String>,
+        #[arg(long)]
+        with: Vec<String>,
+        #[arg(long)]
+        without: Vec<String>,
         #[arg(short, long)]
         yes: bool,
     },
     Remove {
         package: String,
+        #[arg(short, long)]
+        force: bool,
+    },
+    Autoremove {
+        #[arg(short, long)]
+        yes: bool,
     },
     Search {
         query: String,
     },
-    List,
+    List {
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long)]
+        build_system: Option<String>,
+        #[arg(long)]
+        outdated: bool,
+        #[arg(long)]
+        sort: Option<String>,
+        #[arg(short, long)]
+        reverse: bool,
+    },
+    Info {
+        package: String,
+        #[arg(long)]
+        remote: bool,
+    },
+    Files {
+        package: String,
+    },
+    Owns {
+        path: PathBuf,
+    },
+    Verify {
+        package: Option<String>,
+    },
+    Doctor {
+        #[arg(long)]
+        fix: bool,
+    },
+    Outdated,
     Upgrade {
         package: Option<String>,
         #[arg(short, long)]
@@ -45,4 +87,11 @@ pub enum Commands {
         #[arg(short, long)]
         file: Option<PathBuf>,
     },
+    Lint {
+        path: Option<PathBuf>,
+        #[arg(long)]
+        schema: bool,
+        #[arg(long)]
+        migrate: bool,
+    },
 }
@@ -36,12 +36,11 @@ pub fn convert(file: Option<&Path>) {
     let value: Value = toml::from_str(&cargo_toml).expect("Invalid TOML format");
 
     let mut radon_json = Map::new();
+    radon_json.insert("schema".to_string(), JsonValue::from(crate::manifest::SCHEMA_VERSION));
     radon_json.insert("build_system".to_string(), JsonValue::String("cargo".to_string()));
 
-    if let Some(package) = value.get("package") {
-        if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
-            radon_json.insert("name".to_string(), JsonValue::String(name.to_string()));
-        }
+    if let Some(name) = value.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()) {
+        radon_json.insert("name".to_string(), JsonValue::String(name.to_string()));
     }
 
     if let Some(dependencies) = value.get("dependencies") {
@@ -0,0 +1,221 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use ansi_term::Colour::{Green, Red, Yellow};
+use crate::deps::{self, Requirement};
+use crate::utils::{self, InstalledPackage};
+
+struct Report {
+    fix: bool,
+    problems: usize,
+}
+
+impl Report {
+    fn ok(&self, message: &str) {
+        println!("[{}] {}", Green.paint(" ok "), message);
+    }
+
+    fn problem(&mut self, message: &str, suggestion: &str) {
+        self.problems += 1;
+        println!("[{}] {}", Red.paint("fail"), message);
+        println!("       {} {}", Yellow.paint("fix:"), suggestion);
+    }
+
+    // Safe fixes only touch radon's own scratch and bookkeeping directories
+    fn fixable(&mut self, message: &str, suggestion: &str, apply: impl FnOnce() -> bool) {
+        if !self.fix {
+            self.problem(message, &format!("{} (or run `radon doctor --fix`)", suggestion));
+            return;
+        }
+        if apply() {
+            println!("[{}] {}", Green.paint("done"), message);
+        } else {
+            self.problem(message, suggestion);
+        }
+    }
+}
+
+pub fn doctor(fix: bool) {
+    let mut report = Report { fix, problems: 0 };
+
+    check_tools(&mut report);
+    check_path(&mut report);
+    if let Some(installed) = check_database(&mut report) {
+        check_packages(&mut report, &installed);
+        check_buildfiles(&mut report, &installed);
+    }
+    check_tmp(&mut report);
+
+    if report.problems == 0 {
+        println!("{}", Green.paint("~> No problems found"));
+    } else {
+        println!("{}", Yellow.paint(format!("~> {} problems found", report.problems)));
+        std::process::exit(1);
+    }
+}
+
+fn check_tools(report: &mut Report) {
+    let privilege_cmd = utils::get_privilege_command();
+    for tool in ["git", "patch", privilege_cmd.as_str()] {
+        if deps::command_exists(tool) {
+            report.ok(&format!("{} is available", tool));
+            continue;
+        }
+
+        let packages = Requirement::parse(tool).ok()
+            .and_then(|req| deps::distro_packages(&req, utils::detect_distro()))
+            .unwrap_or_else(|| vec![tool.to_string()]);
+        report.problem(
+            &format!("{} is not installed", tool),
+            &format!("install it with `{} {}`", utils::detect_package_manager(), packages.join(" ")),
+        );
+    }
+}
+
+fn check_path(report: &mut Report) {
+    let path = env::var("PATH").unwrap_or_default();
+    let entries: Vec<PathBuf> = env::split_paths(&path).collect();
+    let home = env::var("HOME").unwrap_or_default();
+
+    for dir in [PathBuf::from("/usr/local/bin"), Path::new(&home).join(".local/bin")] {
+        if entries.contains(&dir) {
+            report.ok(&format!("{} is on PATH", dir.display()));
+        } else {
+            report.problem(
+                &format!("{} is not on PATH", dir.display()),
+                &format!("add `export PATH=\"{}:$PATH\"` to your shell profile", dir.display()),
+            );
+        }
+    }
+}
+
+fn check_database(report: &mut Report) -> Option<Vec<InstalledPackage>> {
+    let path = Path::new("/etc/radon/installed.yaml");
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) if !path.exists() => {
+            report.ok("installed.yaml does not exist yet");
+            return Some(Vec::new());
+        }
+        Err(e) => {
+            report.problem(&format!("Can't read {}: {}", path.display(), e), "check the file's permissions");
+            return None;
+        }
+    };
+
+    if content.trim().is_empty() {
+        report.ok("installed.yaml is empty");
+        return Some(Vec::new());
+    }
+    match serde_yaml::from_str::<Vec<InstalledPackage>>(&content) {
+        Ok(installed) => {
+            report.ok(&format!("installed.yaml lists {} packages", installed.len()));
+            Some(installed)
+        }
+        Err(e) => {
+            report.problem(
+                &format!("installed.yaml can't be parsed: {}", e),
+                "fix the entry by hand, radon treats an unparseable file as having no packages",
+            );
+            None
+        }
+    }
+}
+
+fn check_packages(report: &mut Report, installed: &[InstalledPackage]) {
+    let mut healthy = true;
+    for pkg in installed {
+        let missing: Vec<String> = pkg.installed_files()
+            .into_iter()
+            .filter(|f| !Path::new(f).exists())
+            .collect();
+        if !missing.is_empty() {
+            healthy = false;
+            report.problem(
+                &format!("{} is missing {}", pkg.name, missing.join(", ")),
+                &format!("reinstall it, or drop the record with `radon remove {}`", pkg.name),
+            );
+        }
+
+        let broken: Vec<&String> = pkg.libraries.iter()
+            .filter(|lib| !Path::new(lib).exists())
+            .collect();
+        if !broken.is_empty() {
+            healthy = false;
+            let broken: Vec<&str> = broken.iter().map(|lib| lib.as_str()).collect();
+            report.problem(
+                &format!("{} links against removed libraries: {}", pkg.name, broken.join(", ")),
+                &format!("rebuild it with `radon upgrade {}`", pkg.name),
+            );
+        }
+    }
+    if healthy {
+        report.ok("every package's files and libraries are present");
+    }
+}
+
+fn check_buildfiles(report: &mut Report, installed: &[InstalledPackage]) {
+    let buildfiles = Path::new("/var/lib/radon/buildfiles");
+    let stale: Vec<PathBuf> = fs::read_dir(buildfiles)
+        .map(|entries| entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .filter(|p| {
+                let name = p.file_name().unwrap_or_default().to_string_lossy();
+                !installed.iter().any(|pkg| pkg.name == name)
+            })
+            .collect())
+        .unwrap_or_default();
+
+    if stale.is_empty() {
+        report.ok("no stale build files");
+        return;
+    }
+
+    let names: Vec<String> = stale.iter()
+        .map(|p| p.file_name().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    report.fixable(
+        &format!("stale build files for uninstalled packages: {}", names.join(", ")),
+        &format!("remove them from {}", buildfiles.display()),
+        || Command::new(utils::get_privilege_command())
+            .arg("rm")
+            .arg("-rf")
+            .args(&stale)
+            .status()
+            .is_ok_and(|s| s.success()),
+    );
+}
+
+fn check_tmp(report: &mut Report) {
+    let tmp = Path::new("/tmp/radon");
+    let size = dir_size(tmp);
+    if size == 0 {
+        report.ok("no leftover builds in /tmp/radon");
+        return;
+    }
+
+    report.fixable(
+        &format!("leftover builds in {} take up {}", tmp.display(), utils::format_size(size)),
+        &format!("remove {}", tmp.display()),
+        || fs::remove_dir_all(tmp).is_ok(),
+    );
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| entries
+            .filter_map(|e| e.ok())
+            .map(|e| dir_size(&e.path()))
+            .sum())
+        .unwrap_or(0)
+}
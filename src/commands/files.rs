@@ -0,0 +1,50 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ansi_term::Colour::{Red, Yellow};
+use crate::utils::{self, InstalledPackage};
+
+pub fn files(package: &str) {
+    let installed = utils::get_installed_packages();
+    let Some(pkg) = installed.iter().find(|p| p.name == package) else {
+        eprintln!("{}: Package '{}' not found", Red.paint("Error"), package);
+        std::process::exit(1);
+    };
+
+    for file in pkg.installed_files() {
+        if Path::new(&file).exists() {
+            println!("{}", file);
+        } else {
+            println!("{} {}", file, Yellow.paint("(missing)"));
+        }
+    }
+}
+
+pub fn owns(path: &Path) {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    };
+    let canonical = fs::canonicalize(&absolute).ok();
+
+    let installed = utils::get_installed_packages();
+    let owners: Vec<&InstalledPackage> = installed.iter()
+        .filter(|pkg| pkg.installed_files().iter().any(|file| {
+            let file = PathBuf::from(file);
+            file == absolute
+                || canonical.as_ref().is_some_and(|c| fs::canonicalize(&file).is_ok_and(|f| f == *c))
+        }))
+        .collect();
+
+    if owners.is_empty() {
+        eprintln!("{} is not owned by any package", absolute.display());
+        std::process::exit(1);
+    }
+    for pkg in owners {
+        match &pkg.version {
+            Some(version) => println!("{} is owned by {} {}", absolute.display(), pkg.name, version),
+            None => println!("{} is owned by {}", absolute.display(), pkg.name),
+        }
+    }
+}
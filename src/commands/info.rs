@@ -0,0 +1,125 @@
+use std::fs;
+use reqwest::blocking::Client;
+use reqwest::header;
+use serde_json::Value;
+use ansi_term::Colour::{Green, Red, Yellow};
+use ansi_term::Style;
+use crate::utils::{self, InstalledPackage};
+
+pub fn info(package: &str, remote: bool) {
+    let installed = utils::get_installed_packages();
+    let Some(pkg) = installed.iter().find(|p| p.name == package) else {
+        eprintln!("{}: Package '{}' not found", Red.paint("Error"), package);
+        std::process::exit(1);
+    };
+
+    let list = |items: &[String]| if items.is_empty() { "-".to_string() } else { items.join(", ") };
+    let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+    field("Name", &pkg.name);
+    field("Version", &optional(&pkg.version));
+    field("Source", pkg.source.as_deref().unwrap_or("github"));
+    field("Repository", &optional(&pkg.repo_url));
+    field("Commit", &optional(&pkg.commit));
+    field("Branch", &optional(&pkg.branch));
+    field("Build system", &pkg.build_system);
+    field("Build file", &optional(&pkg.build_file));
+    field("Build file hash", &optional(&pkg.hash));
+    field("Flags", &list(&pkg.flags));
+    field("Patches", &list(&pkg.patches));
+    field("Enabled", &list(&pkg.with));
+    field("Disabled", &list(&pkg.without));
+    field("Installed", &optional(&pkg.installed));
+    field("Reason", if pkg.as_dependency { "dependency" } else { "explicit" });
+    field("Depends on", &list(&pkg.depends));
+    field("Required by", &list(&pkg.required_by));
+    field("Libraries", &list(&pkg.libraries));
+    field("Location", &pkg.location);
+
+    let files = pkg.installed_files();
+    let sizes: Vec<Option<u64>> = files.iter()
+        .map(|f| fs::metadata(f).ok().map(|m| m.len()))
+        .collect();
+    let total: u64 = sizes.iter().flatten().sum();
+
+    println!("{} ({}, {})", Style::new().bold().paint("Files"), files.len(), utils::format_size(total));
+    for (file, size) in files.iter().zip(&sizes) {
+        match size {
+            Some(size) => println!("  {:>10}  {}", utils::format_size(*size), file),
+            None => println!("  {}  {}", Yellow.paint(format!("{:>10}", "missing")), file),
+        }
+    }
+
+    if remote {
+        print_remote(pkg);
+    }
+}
+
+fn field(label: &str, value: &str) {
+    let label = format!("{:<16}", format!("{}:", label));
+    println!("{} {}", Style::new().bold().paint(label), value);
+}
+
+fn print_remote(pkg: &InstalledPackage) {
+    println!();
+    println!("{}", Green.paint("~> Upstream"));
+
+    let Some(url) = &pkg.repo_url else {
+        eprintln!("{}: No repository recorded for {}, reinstall it to enable --remote", Yellow.paint("Warning"), pkg.name);
+        return;
+    };
+
+    match fetch_description(pkg.source.as_deref().unwrap_or("github"), url) {
+        Some(description) => field("Description", &description),
+        None => field("Description", "-"),
+    }
+
+    match utils::remote_commit(pkg) {
+        Some(sha) => {
+            let state = if pkg.commit.as_deref() == Some(sha.as_str()) {
+                Green.paint("up to date")
+            } else {
+                Yellow.paint("differs from installed")
+            };
+            field("Latest commit", &format!("{} ({})", sha, state));
+        }
+        None => field("Latest commit", "-"),
+    }
+
+    let mut tags: Vec<String> = utils::ls_remote(url, &["--tags", "--refs"], &[])
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(_, name)| name.strip_prefix("refs/tags/").map(|t| t.to_string()))
+        .collect();
+    tags.sort_by(|a, b| utils::compare_versions(b, a));
+    if tags.is_empty() {
+        field("Tags", "-");
+    } else {
+        let shown: Vec<&str> = tags.iter().take(10).map(|t| t.as_str()).collect();
+        let more = tags.len().saturating_sub(shown.len());
+        let suffix = if more > 0 { format!(" (+{} more)", more) } else { String::new() };
+        field("Tags", &format!("{}{}", shown.join(", "), suffix));
+    }
+}
+
+fn fetch_description(forge: &str, url: &str) -> Option<String> {
+    let domain = utils::forge_domain(forge)?;
+    let repo = url.strip_prefix(&format!("https://{}/", domain))?;
+    let api = match forge {
+        "gitlab" => format!("https://gitlab.com/api/v4/projects/{}", urlencoding::encode(repo)),
+        "codeberg" => format!("https://codeberg.org/api/v1/repos/{}", repo),
+        _ => format!("https://api.github.com/repos/{}", repo),
+    };
+
+    let json: Value = Client::new()
+        .get(&api)
+        .header(header::USER_AGENT, "radon-pkg-manager")
+        .send()
+        .ok()
+        .filter(|resp| resp.status().is_success())?
+        .json()
+        .ok()?;
+    json["description"].as_str()
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_string())
+}
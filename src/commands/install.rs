@@ -1,15 +1,20 @@
 use std::fs;
 use std::env;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, ExitStatus, Stdio};
 use std::io::{self, Write};
 use std::time::Instant;
 use ansi_term::Colour::{Green, Red, Yellow};
-use toml::{Value, map::Map};
 use sha2::{Sha256, Digest};
 use toml::Table;
+use crate::deps::{self, Requirement};
+use crate::infer;
+use crate::manifest::{self, BuildStep, Manifest};
+use crate::resolve::{self, PackageRef};
 use crate::utils;
 
+#[allow(clippy::too_many_arguments)]
 pub fn install(
     packages: &[String],
     gitlab: bool,
@@ -18,13 +23,89 @@ pub fn install(
     branch: Option<&str>,
     patches: Option<&Path>,
     flags: &[String],
+    with: &[String],
+    without: &[String],
     yes: bool,
 ) {
-    for package in packages {
-        install_single(package, gitlab, codeberg, local, branch, patches, flags, yes);
+    let forge = if codeberg {
+        "codeberg"
+    } else if gitlab {
+        "gitlab"
+    } else {
+        "github"
+    };
+
+    let roots: Vec<PackageRef> = packages.iter()
+        .map(|p| PackageRef {
+            forge: forge.to_string(),
+            repo: p.clone(),
+            git_ref: branch.map(|b| b.to_string()),
+        })
+        .collect();
+
+    println!("\x1b[1m~> Resolving dependencies\x1b[0m");
+    let plan = match resolve::resolve(&roots) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("{}: {}", Red.paint("Error"), e);
+            return;
+        }
+    };
+
+    let dependencies: Vec<String> = plan.iter()
+        .filter(|p| p.as_dependency)
+        .map(|p| p.package.to_string())
+        .collect();
+    if !dependencies.is_empty() {
+        println!("~> Also installing dependencies: {}", dependencies.join(", "));
+    }
+
+    for entry in &plan {
+        let pkg = &entry.package;
+        let (patches, flags, with, without) = if entry.as_dependency {
+            (None, &[][..], &[][..], &[][..])
+        } else {
+            (patches, flags, with, without)
+        };
+        // A package only stays a dependency if it wasn't already installed explicitly
+        let was_dependency = utils::get_installed_packages()
+            .iter()
+            .find(|p| p.name == pkg.name())
+            .is_none_or(|p| p.as_dependency);
+        let ok = install_single(
+            &pkg.repo,
+            pkg.forge == "gitlab",
+            pkg.forge == "codeberg",
+            local,
+            pkg.git_ref.as_deref(),
+            patches,
+            flags,
+            with,
+            without,
+            yes,
+        );
+
+        if !ok {
+            if entry.as_dependency {
+                eprintln!("{}: Failed to install dependency {}, stopping", Red.paint("Error"), pkg);
+                return;
+            }
+            continue;
+        }
+
+        let mut installed = utils::get_installed_packages();
+        if let Some(record) = installed.iter_mut().find(|p| p.name == pkg.name()) {
+            let as_dependency = entry.as_dependency && was_dependency;
+            if record.as_dependency != as_dependency || record.depends != entry.depends {
+                record.as_dependency = as_dependency;
+                record.depends = entry.depends.clone();
+                utils::save_installed_packages(&installed);
+            }
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn install_single(
     package: &str,
     gitlab: bool,
@@ -33,12 +114,13 @@ pub fn install_single(
     branch: Option<&str>,
     patches: Option<&Path>,
     flags: &[String],
+    with: &[String],
+    without: &[String],
     yes: bool,
-) {
+) -> bool {
     let start = Instant::now();
     let tmp = Path::new("/tmp/radon");
     let builds = tmp.join("builds");
-    let etc = Path::new("/etc/radon");
 
     for dir in [tmp, &builds] {
         if !dir.exists() {
@@ -54,13 +136,9 @@ pub fn install_single(
         None
     };
     
-    let (source_str, domain) = match source {
-        Some("gitlab") => ("gitlab", "gitlab.com"),
-        Some("codeberg") => ("codeberg", "codeberg.org"),
-        _ => ("github", "github.com")
-    };
+    let domain = utils::forge_domain(source.unwrap_or("github")).unwrap();
 
-    let repo = package.split('/').last().unwrap();
+    let repo = package.split('/').next_back().unwrap();
     let build_dir = builds.join(repo);
 
     if build_dir.exists() {
@@ -68,11 +146,12 @@ pub fn install_single(
     }
 
     println!("\x1b[1m~> Cloning repository: {}\x1b[0m", package);
+    let repo_url = format!("https://{}/{}", domain, package);
     let mut git_clone = Command::new("git");
     git_clone
         .arg("clone")
         .arg("--depth=1")
-        .arg(format!("https://{}/{}", domain, package));
+        .arg(&repo_url);
 
     if let Some(b) = branch {
         git_clone.arg("--branch").arg(b);
@@ -86,42 +165,91 @@ pub fn install_single(
 
     if !status.success() {
         eprintln!("{}", Red.paint("Failed to clone repository"));
-        return;
+        return false;
     }
 
-    if let Some(patches_dir) = patches {
-        apply_patches(&build_dir, patches_dir);
-    }
+    let commit = git_output(&build_dir, &["rev-parse", "HEAD"]);
+    let tracked_branch = branch.map(|b| b.to_string())
+        .or_else(|| git_output(&build_dir, &["rev-parse", "--abbrev-ref", "HEAD"]));
+
+    let applied_patches = match patches {
+        Some(patches_dir) => apply_patches(&build_dir, patches_dir),
+        None => Vec::new(),
+    };
 
     println!("\x1b[1m~> Searching for build file\x1b[0m");
     let makefiles = ["Makefile", "makefile", "GNUMakefile"];
     let has_makefile = makefiles.iter().any(|f| build_dir.join(f).exists());
 
-    let radon_json_path = build_dir.join("radon.json");
-    let (build_system, mut deps, custom_flags) = if radon_json_path.exists() {
-        parse_radon_json(&radon_json_path)
+    let manifest_path = manifest::find(&build_dir);
+    let nimble_file = find_file_with_extension(&build_dir, "nimble");
+    let cabal_file = find_file_with_extension(&build_dir, "cabal");
+    let manifest = if let Some(manifest_path) = &manifest_path {
+        match manifest::load(manifest_path) {
+            Ok(mut manifest) => {
+                let matched = manifest.apply_platform(&utils::detect_platform());
+                if !matched.is_empty() {
+                    println!("~> Matched conditions: {}", matched.join(", "));
+                }
+                Some(manifest)
+            }
+            Err(errors) => {
+                eprintln!("{}: Invalid manifest {}", Red.paint("Error"),
+                    manifest_path.file_name().unwrap().to_string_lossy());
+                for e in errors {
+                    eprintln!("  {}", e);
+                }
+                return false;
+            }
+        }
+    } else {
+        None
+    };
+
+    let (build_system, deps, custom_flags) = if let Some(m) = &manifest {
+        (m.build_system.clone(), m.system_dependencies(), m.flags.clone())
     } else if has_makefile {
         ("make".to_string(), parse_make_deps(&build_dir, &makefiles), vec![])
-    } else if build_dir.join("configure").exists() {
+    } else if find_configure_script(&build_dir).is_some() {
         ("autotools".to_string(), parse_autotools_deps(&build_dir), vec![])
     } else if build_dir.join("Cargo.toml").exists() {
-        ("cargo".to_string(), parse_cargo_deps(&build_dir), vec![])
+        ("cargo".to_string(), vec![], vec![])
     } else if build_dir.join("CMakeLists.txt").exists() {
         ("cmake".to_string(), vec!["cmake".to_string()], vec![])
     } else if build_dir.join("meson.build").exists() {
         ("meson".to_string(), vec!["meson".to_string(), "ninja".to_string()], vec![])
     } else if build_dir.join("build.ninja").exists() {
         ("ninja".to_string(), vec!["ninja".to_string()], vec![])
-    } else if build_dir.join("*.nimble").exists() {
+    } else if nimble_file.is_some() {
         ("nimble".to_string(), vec!["nim".to_string(), "nimble".to_string()], vec![])
     } else if build_dir.join("stack.yaml").exists() {
         ("stack".to_string(), vec!["stack".to_string()], vec![])
+    } else if cabal_file.is_some() || build_dir.join("cabal.project").exists() {
+        ("cabal".to_string(), vec!["cabal".to_string(), "ghc".to_string()], vec![])
+    } else if build_dir.join("dune-project").exists() {
+        ("dune".to_string(), vec!["opam".to_string(), "dune".to_string()], vec![])
     } else {
         eprintln!("{}", Red.paint("No build system found"));
-        return;
+        return false;
+    };
+
+    let manifest = manifest.unwrap_or_default();
+
+    let enabled_options = match manifest.resolve_options(with, without) {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            eprintln!("{}: {}", Red.paint("Error"), e);
+            return false;
+        }
     };
 
     let mut final_flags = custom_flags;
+    let default_features = if build_system == "cargo" {
+        cargo_default_features(&build_dir)
+    } else {
+        Vec::new()
+    };
+    final_flags.extend(manifest.option_flags(&build_system, &enabled_options, &default_features));
     final_flags.extend(flags.iter().cloned());
 
     println!("~> Build system: {}", match build_system.as_str() {
@@ -133,67 +261,100 @@ pub fn install_single(
         "ninja" => Green.paint("Ninja"),
         "nimble" => Green.paint("Nimble"),
         "stack" => Green.paint("Stack"),
+        "cabal" => Green.paint("Cabal"),
+        "dune" => Green.paint("Dune"),
+        "custom" => Green.paint("Custom"),
         _ => unreachable!()
     });
 
     let build_file = match build_system.as_str() {
         "make" => makefiles.iter().find(|f| build_dir.join(f).exists()).map(|f| f.to_string()),
-        "autotools" => Some("configure".to_string()),
+        "autotools" => find_configure_script(&build_dir).map(|f| f.to_string()),
         "cargo" => Some("Cargo.toml".to_string()),
         "cmake" => Some("CMakeLists.txt".to_string()),
         "meson" => Some("meson.build".to_string()),
         "ninja" => Some("build.ninja".to_string()),
-        "nimble" => build_dir.join("*.nimble").exists().then(|| "*.nimble".to_string()),
+        "nimble" => nimble_file.as_ref()
+            .and_then(|p| p.file_name())
+            .map(|f| f.to_string_lossy().to_string()),
         "stack" => Some("stack.yaml".to_string()),
+        "cabal" => cabal_file.as_ref()
+            .and_then(|p| p.file_name())
+            .map(|f| f.to_string_lossy().to_string())
+            .or_else(|| Some("cabal.project".to_string())),
+        "dune" => Some("dune-project".to_string()),
+        "custom" => manifest_path.as_ref()
+            .and_then(|p| p.file_name())
+            .map(|f| f.to_string_lossy().to_string()),
         _ => None,
     };
 
+    let prefix = if local {
+        let home = env::var("HOME").expect("HOME environment variable not set");
+        PathBuf::from(home).join(".local")
+    } else {
+        PathBuf::from("/usr/local")
+    };
+    let name = manifest.name.clone().unwrap_or_else(|| repo.to_string());
+
+    if !manifest.options.is_empty() {
+        println!("~> Options:");
+        for (option_name, option) in &manifest.options {
+            let state = if enabled_options.contains(option_name) { Green.paint("on") } else { Yellow.paint("off") };
+            match &option.description {
+                Some(description) => println!("  {} [{}] {}", option_name, state, description),
+                None => println!("  {} [{}]", option_name, state),
+            }
+        }
+    }
+
+    let inferred: Vec<String> = infer::infer_system_deps(&build_dir, &build_system)
+        .into_iter()
+        .filter(|req| !deps.contains(req))
+        .collect();
+    if !inferred.is_empty() {
+        println!("~> Inferred system requirements:");
+        for req in &inferred {
+            match Requirement::parse(req).and_then(|r| r.check()) {
+                Ok(()) => println!("  {} [{}]", req, Green.paint("found")),
+                Err(reason) => println!("  {} [{}] {}", req, Yellow.paint("missing"), reason),
+            }
+        }
+    }
+
     if !yes {
-        if let Some(file) = &build_file {
-            let file_path = if file == "*.nimble" {
-                let nimble_files: Vec<_> = fs::read_dir(&build_dir)
-                    .unwrap()
-                    .filter_map(|e| e.ok())
-                    .map(|e| e.path())
-                    .filter(|p| p.extension().map(|e| e == "nimble").unwrap_or(false))
-                    .collect();
-                if nimble_files.is_empty() {
-                    None
-                } else {
-                    nimble_files.get(0).cloned()
-                }
-            } else {
-                Some(build_dir.join(file))
-            };
-
-            if let Some(file_path) = file_path {
-                if file_path.exists() {
-                    println!("~> Build file: {}", file);
-                    let status = Command::new("less")
-                        .arg(&file_path)
-                        .status();
-
-                    if status.is_err() || !status.unwrap().success() {
-                        let _ = Command::new("cat")
-                            .arg(&file_path)
-                            .status();
-                    }
-
-                    print!("~> Proceed with build? [Y/n] ");
-                    io::stdout().flush().unwrap();
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input).unwrap();
-
-                    if input.trim().eq_ignore_ascii_case("n") {
-                        println!("{}", Yellow.paint("Build cancelled by user"));
-                        return;
-                    }
-                }
+        print_manifest_plan(&manifest, &build_dir, &manifest_entries(&manifest, &build_dir, &prefix, &name));
+    }
+
+    if !yes && let Some(file) = &build_file {
+        let file_path = build_dir.join(file);
+        if file_path.exists() {
+            println!("~> Build file: {}", file);
+            let status = Command::new("less")
+                .arg(&file_path)
+                .status();
+
+            if status.is_err() || !status.unwrap().success() {
+                let _ = Command::new("cat")
+                    .arg(&file_path)
+                    .status();
+            }
+
+            print!("~> Proceed with build? [Y/n] ");
+            io::stdout().flush().unwrap();
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).unwrap();
+
+            if input.trim().eq_ignore_ascii_case("n") {
+                println!("{}", Yellow.paint("Build cancelled by user"));
+                return false;
             }
         }
     }
 
-    utils::check_deps(&deps);
+    if !utils::check_deps(&deps, yes) {
+        return false;
+    }
 
     println!("~> Building with flags: {:?}", final_flags);
     let build_status = match build_system.as_str() {
@@ -211,6 +372,31 @@ pub fn install_single(
             cmd.status().expect("Make command failed")
         }
         "autotools" => {
+            if !build_dir.join("configure").exists() {
+                println!("~> Generating configure script");
+                let mut bootstrap_cmd = if build_dir.join("autogen.sh").exists() {
+                    script_command(&build_dir, "autogen.sh")
+                } else if build_dir.join("bootstrap").exists() {
+                    script_command(&build_dir, "bootstrap")
+                } else {
+                    let mut cmd = Command::new("autoreconf");
+                    cmd.arg("-fi");
+                    cmd
+                };
+
+                let bootstrap_status = bootstrap_cmd
+                    .env("NOCONFIGURE", "1")
+                    .current_dir(&build_dir)
+                    .stdout(Stdio::null())
+                    .status()
+                    .expect("Bootstrap command failed");
+
+                if !bootstrap_status.success() || !build_dir.join("configure").exists() {
+                    eprintln!("{}", Red.paint("Failed to generate configure script"));
+                    return false;
+                }
+            }
+
             let configure_status = Command::new("./configure")
                 .args(&final_flags)
                 .current_dir(&build_dir)
@@ -219,7 +405,7 @@ pub fn install_single(
             
             if !configure_status.success() {
                 eprintln!("{}", Red.paint("Configure failed"));
-                return;
+                return false;
             }
             
             Command::new("make")
@@ -240,18 +426,15 @@ pub fn install_single(
                 .current_dir(&cmake_build_dir)
                 .stdout(Stdio::null());
 
-            let cmake_status = cmake_cmd.status();
-
-            if cmake_status.is_err() || !cmake_status.as_ref().unwrap().success() {
-                Command::new("cmake")
+            match cmake_cmd.status() {
+                Ok(status) if status.success() => status,
+                _ => Command::new("cmake")
                     .args(&final_flags)
                     .arg("..")
                     .current_dir(&cmake_build_dir)
                     .stdout(Stdio::null())
                     .status()
-                    .expect("CMake command failed")
-            } else {
-                cmake_status.unwrap()
+                    .expect("CMake command failed"),
             }
         }
         "cargo" => {
@@ -325,95 +508,172 @@ pub fn install_single(
                 .status()
                 .expect("Stack command failed")
         }
+        "cabal" => {
+            let staging = build_dir.join("cabal-bin");
+            fs::create_dir_all(&staging).expect("Failed to create staging dir");
+
+            Command::new("cabal")
+                .arg("install")
+                .arg("--installdir")
+                .arg(&staging)
+                .arg("--install-method=copy")
+                .arg("--overwrite-policy=always")
+                .args(&final_flags)
+                .current_dir(&build_dir)
+                .stdout(Stdio::null())
+                .status()
+                .expect("Cabal command failed")
+        }
+        "dune" => {
+            Command::new("dune")
+                .arg("build")
+                .arg("@install")
+                .arg("--profile")
+                .arg("release")
+                .args(&final_flags)
+                .current_dir(&build_dir)
+                .stdout(Stdio::null())
+                .status()
+                .expect("Dune command failed")
+        }
+        "custom" => run_build_steps(&build_dir, &manifest.build, &final_flags),
         _ => unreachable!()
     };
 
     if !build_status.success() {
         eprintln!("{}", Red.paint("Build failed"));
-        return;
+        return false;
     }
 
     println!("~> Installing...");
-    let bin_path = find_binary_path(&build_dir, repo, &build_system);
+    let dest = prefix.join("bin");
 
-    if bin_path.is_none() {
+    let mut entries = manifest_entries(&manifest, &build_dir, &prefix, &name);
+    if manifest.binaries.is_empty() && build_system != "custom" {
+        entries.extend(
+            find_binaries(&build_dir, repo, &build_system, nimble_file.as_deref())
+                .into_iter()
+                .map(|bin_path| {
+                    let dest_path = dest.join(bin_path.file_name().unwrap());
+                    (bin_path, dest_path, "755".to_string())
+                })
+        );
+    }
+
+    if entries.is_empty() {
         eprintln!("{}: Failed to find built binary", Red.paint("Error"));
-        return;
+        return false;
     }
-    let bin_path = bin_path.unwrap();
 
-    let dest = if local {
-        let home = env::var("HOME").expect("HOME environment variable not set");
-        let path = PathBuf::from(home).join(".local/bin");
-        if !path.exists() {
-            fs::create_dir_all(&path).expect("Failed to create local bin directory");
+    if let Some((missing, _, _)) = entries.iter().find(|(src, _, _)| !src.exists()) {
+        eprintln!("{}: Built file not found: {}", Red.paint("Error"), missing.display());
+        return false;
+    }
+
+    let mut version = manifest.version.clone();
+    if version.is_none() && build_system == "cargo" {
+        if let Ok(cargo_toml) = fs::read_to_string(build_dir.join("Cargo.toml")) {
+            version = cargo_toml.lines()
+                .find(|l| l.starts_with("version = "))
+                .and_then(|v| v.split('"').nth(1))
+                .map(|s| s.to_string());
         }
-        path
-    } else {
-        PathBuf::from("/usr/local/bin")
-    };
+    } else if version.is_none() && let Some(nimble) = &nimble_file {
+        version = parse_nimble_field(nimble, "version");
+    }
 
     if !local {
         println!("{}", Yellow.paint("WARNING: Installing system-wide"));
     }
 
-    let bin_name = bin_path.file_name().unwrap().to_str().unwrap();
-    let dest_path = dest.join(bin_name);
+    if !utils::run_hook("pre_install", &manifest.pre_install, &build_dir, &prefix, repo, version.as_deref(), !local) {
+        return false;
+    }
 
-    if local {
-        fs::copy(&bin_path, &dest_path)
-            .expect("Failed to copy binary to local directory");
-    } else {
-        Command::new(&utils::get_privilege_command())
-            .arg("install")
-            .arg("-m755")
-            .arg(&bin_path)
-            .arg(&dest_path)
-            .status()
-            .expect("Installation failed");
+    let mut dest_paths = Vec::new();
+    for (src_path, dest_path, mode) in &entries {
+        if local {
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent).expect("Failed to create local install directory");
+            }
+            fs::copy(src_path, dest_path)
+                .expect("Failed to copy file to local directory");
+            let mode = u32::from_str_radix(mode, 8).unwrap_or(0o755);
+            fs::set_permissions(dest_path, fs::Permissions::from_mode(mode))
+                .expect("Failed to set file permissions");
+        } else {
+            Command::new(utils::get_privilege_command())
+                .arg("install")
+                .arg("-D")
+                .arg(format!("-m{}", mode))
+                .arg(src_path)
+                .arg(dest_path)
+                .status()
+                .expect("Installation failed");
+        }
+        println!("~> Installed: {}", dest_path.display());
+        dest_paths.push(dest_path.to_string_lossy().to_string());
     }
 
+    let libraries = check_linked_libraries(&dest_paths);
+    let file_info = dest_paths.iter()
+        .filter_map(|p| match utils::InstalledFile::read(Path::new(p)) {
+            Some(info) => Some((p.clone(), info)),
+            None => {
+                eprintln!("{}: Can't read {} to record its checksum, radon verify will report it", Yellow.paint("Warning"), p);
+                None
+            }
+        })
+        .collect();
+
     if !local {
         let mut installed = utils::get_installed_packages();
+        let previous = installed.iter()
+            .position(|p| p.name == repo)
+            .map(|i| installed.remove(i));
         
         let mut hasher = Sha256::new();
-        if let Some(bf) = &build_file {
-            if let Ok(content) = fs::read(build_dir.join(bf)) {
-                hasher.update(&content);
-            }
+        if let Some(bf) = &build_file
+            && let Ok(content) = fs::read(build_dir.join(bf)) {
+            hasher.update(content);
         }
         let hash = format!("{:x}", hasher.finalize());
         
-        let mut version = None;
-        if build_system == "cargo" {
-            if let Ok(cargo_toml) = fs::read_to_string(build_dir.join("Cargo.toml")) {
-                if let Some(v) = cargo_toml.lines().find(|l| l.starts_with("version = ")) {
-                    version = v.split('"').nth(1).map(|s| s.to_string());
-                }
-            }
-        }
-        
         let pkg = utils::InstalledPackage {
             name: repo.to_string(),
             source: source.map(|s| s.to_string()),
             build_system: build_system.to_string(),
-            location: dest_path.to_string_lossy().to_string(),
+            location: dest_paths.iter()
+                .find(|p| Path::new(p).starts_with(&dest))
+                .unwrap_or(&dest_paths[0])
+                .clone(),
             build_file: build_file.clone(),
             hash: Some(hash),
-            version,
+            version: version.clone(),
+            files: dest_paths,
+            as_dependency: previous.as_ref().is_some_and(|p| p.as_dependency),
+            depends: previous.map(|p| p.depends).unwrap_or_default(),
+            required_by: Vec::new(),
+            libraries,
+            repo_url: Some(repo_url),
+            commit,
+            branch: tracked_branch,
+            flags: flags.to_vec(),
+            patches: applied_patches,
+            installed: Some(utils::timestamp()),
+            with: with.to_vec(),
+            without: without.to_vec(),
+            pre_remove: manifest.pre_remove.clone(),
+            post_remove: manifest.post_remove.clone(),
+            file_info,
         };
         
         installed.push(pkg);
-        
-        let temp_path = Path::new("/tmp").join("radon-installed.yaml");
-        fs::write(&temp_path, serde_yaml::to_string(&installed).unwrap()).unwrap();
-        
-        Command::new(&utils::get_privilege_command())
-            .arg("mv")
-            .arg(&temp_path)
-            .arg("/etc/radon/installed.yaml")
-            .status()
-            .expect("Failed to update package list");
+        utils::save_installed_packages(&installed);
+    }
+
+    if !utils::run_hook("post_install", &manifest.post_install, &build_dir, &prefix, repo, version.as_deref(), !local) {
+        eprintln!("{}: Package was installed but its post_install hook failed", Yellow.paint("Warning"));
     }
 
     println!("{} in {}s", Green.paint("~> INSTALL FINISHED"), start.elapsed().as_secs());
@@ -434,6 +694,17 @@ pub fn install_single(
             )
         );
     }
+
+    true
+}
+
+fn cargo_default_features(build_dir: &Path) -> Vec<String> {
+    fs::read_to_string(build_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<Table>().ok())
+        .and_then(|cargo| cargo.get("features")?.get("default")?.as_array().cloned())
+        .map(|defaults| defaults.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
 }
 
 fn get_cargo_binary_name(build_dir: &Path) -> Option<String> {
@@ -448,31 +719,32 @@ fn get_cargo_binary_name(build_dir: &Path) -> Option<String> {
         Err(_) => return None,
     };
 
-    if let Some(bins) = value.get("bin") {
-        if let Some(bin_array) = bins.as_array() {
-            for bin_entry in bin_array {
-                if let Some(bin_table) = bin_entry.as_table() {
-                    if let Some(name) = bin_table.get("name") {
-                        if let Some(name_str) = name.as_str() {
-                            return Some(name_str.to_string());
-                        }
-                    }
-                }
-            }
-        }
+    let bin_name = value.get("bin")
+        .and_then(|b| b.as_array())
+        .and_then(|bins| bins.iter().find_map(|b| b.get("name").and_then(|n| n.as_str())));
+    if let Some(name) = bin_name {
+        return Some(name.to_string());
     }
 
-    if let Some(package) = value.get("package") {
-        if let Some(package_table) = package.as_table() {
-            if let Some(name) = package_table.get("name") {
-                if let Some(name_str) = name.as_str() {
-                    return Some(name_str.to_string());
-                }
-            }
-        }
-    }
+    value.get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(|s| s.to_string())
+}
 
-    None
+fn find_binaries(build_dir: &Path, repo: &str, build_system: &str, nimble_file: Option<&Path>) -> Vec<PathBuf> {
+    if build_system == "nimble" {
+        return nimble_file
+            .map(|nimble| find_nimble_binaries(build_dir, nimble))
+            .unwrap_or_default();
+    }
+    if build_system == "cabal" {
+        return find_cabal_binaries(build_dir);
+    }
+    if build_system == "dune" {
+        return find_dune_binaries(build_dir);
+    }
+    find_binary_path(build_dir, repo, build_system).into_iter().collect()
 }
 
 fn find_binary_path(build_dir: &Path, repo: &str, build_system: &str) -> Option<PathBuf> {
@@ -501,10 +773,6 @@ fn find_binary_path(build_dir: &Path, repo: &str, build_system: &str) -> Option<
             let build_output_dir = build_dir.join("build");
             find_executable_in_dir(&build_output_dir, repo)
         },
-        "nimble" => {
-            let path = build_dir.join(repo);
-            if path.exists() { Some(path) } else { None }
-        },
         "stack" => {
             let bin_dir = build_dir.join("bin");
             if bin_dir.exists() {
@@ -517,6 +785,78 @@ fn find_binary_path(build_dir: &Path, repo: &str, build_system: &str) -> Option<
     }
 }
 
+fn find_files_with_extension(dir: &Path, ext: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().map(|e| e == ext).unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn find_file_with_extension(dir: &Path, ext: &str) -> Option<PathBuf> {
+    find_files_with_extension(dir, ext).into_iter().next()
+}
+
+fn parse_nimble_value(nimble: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(nimble).ok()?;
+    content.lines()
+        .map(|l| l.trim())
+        .filter_map(|l| l.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.split('#').next().unwrap_or("").trim().to_string())
+}
+
+fn parse_nimble_field(nimble: &Path, key: &str) -> Option<String> {
+    parse_nimble_value(nimble, key)
+        .and_then(|v| v.split('"').nth(1).map(|s| s.to_string()))
+        .filter(|s| !s.is_empty())
+}
+
+fn parse_nimble_bins(nimble: &Path) -> Vec<String> {
+    parse_nimble_value(nimble, "bin")
+        .map(|v| {
+            v.split('"')
+                .skip(1)
+                .step_by(2)
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn find_nimble_binaries(build_dir: &Path, nimble: &Path) -> Vec<PathBuf> {
+    let bin_dir = match parse_nimble_field(nimble, "binDir") {
+        Some(dir) => build_dir.join(dir),
+        None => build_dir.to_path_buf(),
+    };
+
+    let mut bins = parse_nimble_bins(nimble);
+    if bins.is_empty() && let Some(stem) = nimble.file_stem() {
+        bins.push(stem.to_string_lossy().to_string());
+    }
+
+    bins.iter()
+        .filter_map(|bin| {
+            let name = Path::new(bin).file_name()?;
+            if bin_dir.join(bin).is_file() {
+                Some(bin_dir.join(bin))
+            } else if bin_dir.join(name).is_file() {
+                Some(bin_dir.join(name))
+            } else {
+                eprintln!("{}: Declared binary {} was not built", Yellow.paint("Warning"), bin);
+                None
+            }
+        })
+        .collect()
+}
+
 fn find_executable_in_dir(dir: &Path, name: &str) -> Option<PathBuf> {
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.filter_map(|e| e.ok()) {
@@ -525,18 +865,85 @@ fn find_executable_in_dir(dir: &Path, name: &str) -> Option<PathBuf> {
                 if let Some(exec) = find_executable_in_dir(&path, name) {
                     return Some(exec);
                 }
-            } else if path.is_file() {
-                if let Some(filename) = path.file_name() {
-                    if filename == name {
-                        return Some(path);
-                    }
-                }
+            } else if path.is_file() && path.file_name().is_some_and(|f| f == name) {
+                return Some(path);
             }
         }
     }
     None
 }
 
+fn parse_cabal_executables(cabal: &Path) -> Vec<String> {
+    let content = fs::read_to_string(cabal).unwrap_or_default();
+    content.lines()
+        .filter(|l| !l.starts_with(char::is_whitespace))
+        .filter_map(|l| {
+            let mut words = l.split_whitespace();
+            match words.next() {
+                Some(stanza) if stanza.eq_ignore_ascii_case("executable") => {
+                    words.next().map(|name| name.to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn find_cabal_binaries(build_dir: &Path) -> Vec<PathBuf> {
+    let staging = build_dir.join("cabal-bin");
+
+    let mut cabal_files = find_files_with_extension(build_dir, "cabal");
+    if cabal_files.is_empty() && let Ok(entries) = fs::read_dir(build_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry.path().is_dir() {
+                cabal_files.extend(find_files_with_extension(&entry.path(), "cabal"));
+            }
+        }
+    }
+
+    let executables: Vec<String> = cabal_files.iter()
+        .flat_map(|f| parse_cabal_executables(f))
+        .collect();
+
+    if executables.is_empty() {
+        return fs::read_dir(&staging)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_file())
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    executables.iter()
+        .filter_map(|name| {
+            let path = staging.join(name);
+            if path.exists() {
+                Some(path)
+            } else {
+                eprintln!("{}: Declared executable {} was not installed", Yellow.paint("Warning"), name);
+                None
+            }
+        })
+        .collect()
+}
+
+fn find_dune_binaries(build_dir: &Path) -> Vec<PathBuf> {
+    let mut bins: Vec<PathBuf> = fs::read_dir(build_dir.join("_build/install/default/bin"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    bins.sort();
+    bins
+}
+
 fn parse_make_deps(dir: &Path, makefiles: &[&str]) -> Vec<String> {
     let found_file = makefiles.iter()
         .find(|f| dir.join(f).exists())
@@ -557,9 +964,41 @@ fn parse_make_deps(dir: &Path, makefiles: &[&str]) -> Vec<String> {
         .unwrap_or_default()
 }
 
+// Runs a script through the interpreter named in its shebang, so it works
+// without the executable bit and bash scripts aren't handed to dash
+fn script_command(dir: &Path, script: &str) -> Command {
+    let content = fs::read_to_string(dir.join(script)).unwrap_or_default();
+    let interpreter: Vec<&str> = content.lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .map(|line| line.split_whitespace().collect())
+        .unwrap_or_default();
+
+    let mut cmd = match interpreter.split_first() {
+        Some((program, args)) => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        None => Command::new("sh"),
+    };
+    cmd.arg(format!("./{}", script));
+    cmd
+}
+
+fn find_configure_script(dir: &Path) -> Option<&'static str> {
+    ["configure", "configure.ac", "configure.in"]
+        .into_iter()
+        .find(|f| dir.join(f).exists())
+}
+
 fn parse_autotools_deps(dir: &Path) -> Vec<String> {
-    let configure = fs::read_to_string(dir.join("configure")).unwrap_or_default();
+    let script = find_configure_script(dir).unwrap_or("configure");
+    let configure = fs::read_to_string(dir.join(script)).unwrap_or_default();
     let mut deps = Vec::new();
+    if script != "configure" {
+        deps.extend(["autoconf", "automake", "libtoolize"].map(String::from));
+    }
     if configure.contains("PKG_CHECK_MODULES") {
         deps.push("pkg-config".to_string());
     }
@@ -572,55 +1011,172 @@ fn parse_autotools_deps(dir: &Path) -> Vec<String> {
     deps
 }
 
-fn parse_cargo_deps(dir: &Path) -> Vec<String> {
-    let cargo_toml = fs::read_to_string(dir.join("Cargo.toml")).unwrap_or_default();
-    let value = cargo_toml.parse::<Value>().unwrap_or(Value::Table(Map::new()));
+type InstallEntry = (PathBuf, PathBuf, String);
 
-    value.get("package")
-        .and_then(|p| p.get("metadata"))
-        .and_then(|m| m.get("radon"))
-        .and_then(|r| r.get("dependencies"))
-        .and_then(|d| d.as_array())
-        .map(|deps| {
-            deps.iter()
-                .filter_map(|d| d.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default()
+fn collect_files(src: &Path, dest: &Path, mode: &str, entries: &mut Vec<InstallEntry>) {
+    if src.is_dir() {
+        if let Ok(children) = fs::read_dir(src) {
+            let mut children: Vec<PathBuf> = children.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+            children.sort();
+            for child in children {
+                let child_dest = dest.join(child.file_name().unwrap());
+                collect_files(&child, &child_dest, mode, entries);
+            }
+        }
+    } else {
+        entries.push((src.to_path_buf(), dest.to_path_buf(), mode.to_string()));
+    }
 }
 
-fn parse_radon_json(path: &Path) -> (String, Vec<String>, Vec<String>) {
-    let file = std::fs::File::open(path).expect("Failed to open radon.json");
-    let reader = std::io::BufReader::new(file);
-    let json: serde_json::Value = serde_json::from_reader(reader).expect("Invalid radon.json");
-
-    let build_system = json["build_system"]
-        .as_str()
-        .unwrap_or("make")
-        .to_string();
-
-    let deps = json["dependencies"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+fn manifest_entries(manifest: &Manifest, build_dir: &Path, prefix: &Path, name: &str) -> Vec<InstallEntry> {
+    let mut entries = Vec::new();
 
-    let flags = json["flags"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+    for m in &manifest.install {
+        let mode = m.mode.clone().unwrap_or_else(|| {
+            if m.to.starts_with("bin/") { "755".to_string() } else { "644".to_string() }
+        });
+        entries.push((build_dir.join(&m.from), prefix.join(&m.to), mode));
+    }
+
+    for (from, bin_name) in &manifest.binaries {
+        entries.push((build_dir.join(from), prefix.join("bin").join(bin_name), "755".to_string()));
+    }
+
+    let share = prefix.join("share");
+    for page in &manifest.man {
+        let section = manifest::man_section(page).unwrap_or_else(|| "1".to_string());
+        let file_name = Path::new(page).file_name().unwrap();
+        let dest = share.join("man").join(format!("man{}", section)).join(file_name);
+        entries.push((build_dir.join(page), dest, "644".to_string()));
+    }
+
+    let completions = [
+        (&manifest.completions.bash, share.join("bash-completion/completions").join(name)),
+        (&manifest.completions.zsh, share.join("zsh/site-functions").join(format!("_{}", name))),
+        (&manifest.completions.fish, share.join("fish/vendor_completions.d").join(format!("{}.fish", name))),
+    ];
+    for (file, dest) in completions {
+        if let Some(file) = file {
+            entries.push((build_dir.join(file), dest, "644".to_string()));
+        }
+    }
+
+    for file in &manifest.desktop {
+        let dest = share.join("applications").join(Path::new(file).file_name().unwrap());
+        entries.push((build_dir.join(file), dest, "644".to_string()));
+    }
+
+    for (from, to) in &manifest.data {
+        collect_files(&build_dir.join(from), &share.join(to), "644", &mut entries);
+    }
+
+    entries
+}
+
+fn check_linked_libraries(files: &[String]) -> Vec<String> {
+    let mut libraries = Vec::new();
+    let mut unresolved = Vec::new();
+
+    for file in files {
+        let path = Path::new(file);
+        let executable = fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0);
+        if !executable {
+            continue;
+        }
+        let Some(linked) = deps::linked_libraries(path) else {
+            continue;
+        };
+        for (name, resolved) in linked {
+            match resolved {
+                Some(lib) => libraries.push(lib),
+                None => unresolved.push(format!("{} (needed by {})", name, file)),
+            }
+        }
+    }
+
+    if !unresolved.is_empty() {
+        println!("{}: Unresolved shared libraries, the package may fail to start:", Yellow.paint("Warning"));
+        for lib in &unresolved {
+            println!("  - {}", lib);
+        }
+    }
+
+    libraries.sort();
+    libraries.dedup();
+    libraries
+}
+
+fn print_manifest_plan(manifest: &Manifest, build_dir: &Path, entries: &[InstallEntry]) {
+    if !manifest.build.is_empty() {
+        println!("~> Build steps:");
+        for (i, step) in manifest.build.iter().enumerate() {
+            let mut line = format!("  {}. {}", i + 1, step.run);
+            if let Some(dir) = &step.dir {
+                line.push_str(&format!(" (in {})", dir));
+            }
+            println!("{}", line);
+            for (key, value) in &step.env {
+                println!("       {}={}", key, value);
+            }
+        }
+    }
+    for (hook, commands) in manifest.hooks() {
+        if !commands.is_empty() {
+            println!("~> {} hook:", hook);
+            for command in commands {
+                println!("  {}", command);
+            }
+        }
+    }
+    if !entries.is_empty() {
+        println!("~> Install:");
+        for (src, dest, mode) in entries {
+            let src = src.strip_prefix(build_dir).unwrap_or(src);
+            println!("  {} -> {} ({})", src.display(), dest.display(), mode);
+        }
+    }
+}
+
+fn run_build_steps(build_dir: &Path, steps: &[BuildStep], flags: &[String]) -> ExitStatus {
+    let mut status = ExitStatus::default();
+    for (i, step) in steps.iter().enumerate() {
+        println!("~> Step {}/{}: {}", i + 1, steps.len(), step.run);
+        let dir = match &step.dir {
+            Some(dir) => build_dir.join(dir),
+            None => build_dir.to_path_buf(),
+        };
 
-    (build_system, deps, flags)
+        status = Command::new("sh")
+            .arg("-c")
+            .arg(&step.run)
+            .envs(&step.env)
+            .env("RADON_FLAGS", flags.join(" "))
+            .current_dir(&dir)
+            .stdout(Stdio::null())
+            .status()
+            .expect("Build step failed");
+
+        if !status.success() {
+            break;
+        }
+    }
+    status
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
 }
 
-fn apply_patches(build_dir: &Path, patches_dir: &Path) {
+fn apply_patches(build_dir: &Path, patches_dir: &Path) -> Vec<String> {
+    let mut applied = Vec::new();
     let patches: Vec<PathBuf> = fs::read_dir(patches_dir)
         .unwrap()
         .filter_map(|e| e.ok())
@@ -639,8 +1195,13 @@ fn apply_patches(build_dir: &Path, patches_dir: &Path) {
             .status()
             .expect("Failed to apply patch");
 
-        if !status.success() {
+        // Upgrades look the patches up again from wherever radon is run
+        if status.success() {
+            let patch = fs::canonicalize(&patch).unwrap_or(patch);
+            applied.push(patch.to_string_lossy().to_string());
+        } else {
             eprintln!("{}: Failed to apply {}", Red.paint("Error"), patch.display());
         }
     }
+    applied
 }
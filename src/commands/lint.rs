@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+use ansi_term::Colour::{Green, Red, Yellow};
+use crate::manifest;
+
+pub fn lint(path: Option<&Path>, schema: bool, migrate: bool) {
+    if schema {
+        println!("{}", serde_json::to_string_pretty(&manifest::schema()).unwrap());
+        return;
+    }
+
+    let path = match path {
+        Some(p) if !p.is_dir() => p.to_path_buf(),
+        _ => {
+            let dir = path.unwrap_or(Path::new("."));
+            match manifest::find(dir) {
+                Some(found) => found,
+                None => {
+                    eprintln!(
+                        "{}: No {} or Cargo.toml with [package.metadata.radon] in {}",
+                        Red.paint("Error"),
+                        manifest::MANIFEST_FILES.join(", "),
+                        dir.display()
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    if !path.exists() {
+        eprintln!("{}: File not found - {}", Red.paint("Error"), path.display());
+        std::process::exit(1);
+    }
+
+    if migrate {
+        migrate_manifest(&path);
+        return;
+    }
+
+    match manifest::load(&path) {
+        Ok(m) => {
+            println!("{} {} is valid", Green.paint("~>"), path.display());
+            if m.schema.is_none() {
+                println!(
+                    "{}: no schema version set, run `radon lint --migrate` to update to schema {}",
+                    Yellow.paint("Warning"),
+                    manifest::SCHEMA_VERSION
+                );
+            }
+        }
+        Err(errors) => {
+            eprintln!("{} {} has {} problem(s):", Red.paint("~>"), path.display(), errors.len());
+            for e in errors {
+                eprintln!("  {}", e);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn migrate_manifest(path: &Path) {
+    let file_name = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    let content = fs::read_to_string(path).expect("Failed to read file");
+
+    let mut value = match manifest::parse_value(&content, &file_name) {
+        Ok(value) => value,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("{}: {}", Red.paint("Error"), e);
+            }
+            std::process::exit(1);
+        }
+    };
+
+    let changes = manifest::migrate(&mut value);
+    if changes.is_empty() {
+        println!("{} {} is already at schema {}", Green.paint("~>"), path.display(), manifest::SCHEMA_VERSION);
+        return;
+    }
+
+    if let Err(errors) = manifest::validate(&value) {
+        eprintln!("{} migrated {} still has {} problem(s):", Red.paint("~>"), path.display(), errors.len());
+        for e in errors {
+            eprintln!("  {}", e);
+        }
+        std::process::exit(1);
+    }
+
+    for change in &changes {
+        println!("  {}", change);
+    }
+
+    let output = if file_name == "Cargo.toml" || file_name.ends_with(".toml") {
+        toml::to_string_pretty(&value).expect("Failed to serialize TOML")
+    } else if file_name.ends_with(".yaml") || file_name.ends_with(".yml") {
+        serde_yaml::to_string(&value).expect("Failed to serialize YAML")
+    } else {
+        serde_json::to_string_pretty(&value).expect("Failed to serialize JSON") + "\n"
+    };
+
+    if file_name == "Cargo.toml" {
+        println!("{}: radon does not rewrite Cargo.toml, replace the table by hand:\n", Yellow.paint("Note"));
+        println!("[package.metadata.radon]\n{}", output);
+        return;
+    }
+
+    fs::write(path, output).expect("Failed to write manifest");
+    println!("{} Migrated {} to schema {}", Green.paint("~>"), path.display(), manifest::SCHEMA_VERSION);
+}
@@ -1,14 +1,127 @@
-use crate::utils::get_installed_packages;
-use ansi_term::Colour::Green;
+use std::io::{self, IsTerminal};
+use ansi_term::Colour::{Red, Yellow};
+use comfy_table::{Table, ContentArrangement};
+use comfy_table::presets::UTF8_FULL;
+use crate::commands::outdated;
+use crate::utils::{self, InstalledPackage};
+
+const COLUMNS: &[&str] = &[
+    "name", "version", "source", "build_system", "location",
+    "installed", "branch", "commit", "reason", "files",
+];
+
+const DEFAULT_COLUMNS: &[&str] = &["name", "version", "source", "build_system"];
+
+pub struct ListOptions<'a> {
+    pub columns: &'a [String],
+    pub source: Option<&'a str>,
+    pub build_system: Option<&'a str>,
+    pub outdated: bool,
+    pub sort: Option<&'a str>,
+    pub reverse: bool,
+}
+
+pub fn list(options: &ListOptions) {
+    let columns: Vec<&str> = if options.columns.is_empty() {
+        DEFAULT_COLUMNS.to_vec()
+    } else {
+        options.columns.iter().map(|c| c.as_str()).collect()
+    };
+    for column in columns.iter().chain(options.sort.iter()) {
+        if !COLUMNS.contains(column) {
+            eprintln!("{}: Unknown column '{}', expected one of: {}", Red.paint("Error"), column, COLUMNS.join(", "));
+            return;
+        }
+    }
+
+    let mut packages: Vec<InstalledPackage> = utils::get_installed_packages()
+        .into_iter()
+        .filter(|p| options.source.is_none_or(|s| p.source.as_deref().unwrap_or("github") == s))
+        .filter(|p| options.build_system.is_none_or(|b| p.build_system == b))
+        .collect();
+
+    if options.outdated {
+        let results = outdated::check_all(&packages);
+        let mut results = results.iter();
+        packages.retain(|p| match results.next() {
+            Some(Some(available)) => available.outdated,
+            _ if p.repo_url.is_none() || p.commit.is_none() => {
+                eprintln!("{}: Can't check {} for updates, no commit or repository recorded", Yellow.paint("Warning"), p.name);
+                false
+            }
+            _ => {
+                eprintln!("{}: Can't check {} for updates, {} can't be reached", Yellow.paint("Warning"),
+                    p.name, p.repo_url.as_deref().unwrap_or_default());
+                false
+            }
+        });
+    }
+
+    if let Some(key) = options.sort {
+        packages.sort_by(|a, b| match key {
+            "version" => utils::compare_versions(
+                a.version.as_deref().unwrap_or(""),
+                b.version.as_deref().unwrap_or(""),
+            ),
+            "files" => a.installed_files().len().cmp(&b.installed_files().len()),
+            _ => cell(a, key).cmp(&cell(b, key)),
+        });
+    }
+    if options.reverse {
+        packages.reverse();
+    }
+
+    if !io::stdout().is_terminal() {
+        for pkg in &packages {
+            let row: Vec<String> = columns.iter().map(|c| cell(pkg, c)).collect();
+            println!("{}", row.join("\t"));
+        }
+        return;
+    }
 
-pub fn list() {
-    println!("{}", Green.paint("Installed packages:"));
-    let packages = get_installed_packages();
     if packages.is_empty() {
         println!("No packages installed");
-    } else {
-        for pkg in packages {
-            println!("- {} ({})", pkg.name, pkg.build_system);
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(columns.iter().map(|c| header(c)).collect::<Vec<_>>());
+
+    for pkg in &packages {
+        table.add_row(columns.iter().map(|c| cell(pkg, c)).collect::<Vec<_>>());
+    }
+
+    println!("{}", table);
+}
+
+fn header(column: &str) -> String {
+    match column {
+        "build_system" => "Build system".to_string(),
+        _ => {
+            let mut chars = column.chars();
+            chars.next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
         }
     }
 }
+
+fn cell(pkg: &InstalledPackage, column: &str) -> String {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    match column {
+        "name" => pkg.name.clone(),
+        "version" => optional(&pkg.version),
+        "source" => pkg.source.clone().unwrap_or_else(|| "github".to_string()),
+        "build_system" => pkg.build_system.clone(),
+        "location" => pkg.location.clone(),
+        "installed" => optional(&pkg.installed),
+        "branch" => optional(&pkg.branch),
+        "commit" => pkg.commit.as_deref().map(|c| c.chars().take(12).collect()).unwrap_or_else(|| "-".to_string()),
+        "reason" => if pkg.as_dependency { "dependency" } else { "explicit" }.to_string(),
+        "files" => pkg.installed_files().len().to_string(),
+        _ => String::new(),
+    }
+}
@@ -2,5 +2,11 @@ pub mod install;
 pub mod remove;
 pub mod search;
 pub mod list;
+pub mod info;
+pub mod files;
+pub mod verify;
+pub mod doctor;
+pub mod outdated;
 pub mod upgrade;
 pub mod convert;
+pub mod lint;
@@ -0,0 +1,144 @@
+use std::cmp::Ordering;
+use std::io::{self, IsTerminal};
+use std::thread;
+use comfy_table::{Cell, Color, Table, ContentArrangement};
+use comfy_table::presets::UTF8_FULL;
+use crate::utils::{self, InstalledPackage};
+
+// How many ls-remote checks run at once
+const PARALLEL_CHECKS: usize = 8;
+
+pub struct Available {
+    pub tracking: String,
+    pub latest: String,
+    pub outdated: bool,
+    pub tag: bool,
+}
+
+pub fn outdated() {
+    let installed = utils::get_installed_packages();
+    if installed.is_empty() {
+        println!("No packages installed");
+        return;
+    }
+
+    let results = check_all(&installed);
+
+    if !io::stdout().is_terminal() {
+        for (pkg, available) in installed.iter().zip(&results) {
+            let row = match available {
+                Some(a) => [pkg.name.clone(), a.tracking.clone(), installed_label(pkg), a.latest.clone(), status(a).to_string()],
+                None => [pkg.name.clone(), "-".into(), installed_label(pkg), "-".into(), "unknown".into()],
+            };
+            println!("{}", row.join("\t"));
+        }
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Package", "Tracking", "Installed", "Available", "Status"]);
+
+    for (pkg, available) in installed.iter().zip(&results) {
+        let row = match available {
+            Some(a) => {
+                let color = if a.outdated { Color::Yellow } else { Color::Green };
+                vec![
+                    Cell::new(&pkg.name),
+                    Cell::new(&a.tracking),
+                    Cell::new(installed_label(pkg)),
+                    Cell::new(&a.latest),
+                    Cell::new(status(a)).fg(color),
+                ]
+            }
+            None => vec![
+                Cell::new(&pkg.name),
+                Cell::new("-"),
+                Cell::new(installed_label(pkg)),
+                Cell::new("-"),
+                Cell::new("unknown").fg(Color::Red),
+            ],
+        };
+        table.add_row(row);
+    }
+    println!("{}", table);
+
+    let count = results.iter().flatten().filter(|a| a.outdated).count();
+    println!("~> {} of {} packages can be upgraded", count, installed.len());
+    if results.iter().any(|r| r.is_none()) {
+        println!("~> Packages marked unknown have no recorded repository or commit, or their remote can't be reached");
+    }
+}
+
+pub fn check_all(packages: &[InstalledPackage]) -> Vec<Option<Available>> {
+    packages.chunks(PARALLEL_CHECKS)
+        .flat_map(|chunk| thread::scope(|scope| {
+            let handles: Vec<_> = chunk.iter()
+                .map(|pkg| scope.spawn(move || check(pkg)))
+                .collect();
+            handles.into_iter()
+                .map(|h| h.join().unwrap_or(None))
+                .collect::<Vec<_>>()
+        }))
+        .collect()
+}
+
+pub fn check(pkg: &InstalledPackage) -> Option<Available> {
+    let url = pkg.repo_url.as_deref()?;
+    let commit = pkg.commit.as_deref()?;
+    let refs = utils::ls_remote(url, &[], &[])?;
+
+    let tracking = pkg.branch.clone().unwrap_or_else(|| "HEAD".to_string());
+    let is_tag = refs.iter().any(|(_, name)| *name == format!("refs/tags/{}", tracking));
+
+    if !is_tag {
+        let wanted = if tracking == "HEAD" { tracking.clone() } else { format!("refs/heads/{}", tracking) };
+        let (sha, _) = refs.iter().find(|(_, name)| *name == wanted)?;
+        return Some(Available {
+            tracking,
+            latest: short(sha),
+            outdated: sha != commit,
+            tag: false,
+        });
+    }
+
+    // Installed from a tag: look for newer tags that follow the same naming,
+    // skipping pre-releases unless a pre-release is what's installed
+    let allow_prerelease = utils::is_prerelease(&tracking);
+    let prefix: String = tracking.chars().take_while(|c| !c.is_ascii_digit()).collect();
+    let version_of = |tag: &str| tag[prefix.len()..].to_string();
+    let newest = refs.iter()
+        .filter_map(|(_, name)| name.strip_prefix("refs/tags/"))
+        .filter(|tag| !tag.ends_with("^{}"))
+        .filter(|tag| tag.starts_with(&prefix) && tag[prefix.len()..].starts_with(|c: char| c.is_ascii_digit()))
+        .filter(|tag| allow_prerelease || !utils::is_prerelease(&version_of(tag)))
+        .max_by(|a, b| utils::compare_versions(&version_of(a), &version_of(b)))
+        .unwrap_or(&tracking)
+        .to_string();
+
+    Some(Available {
+        outdated: utils::compare_versions(&version_of(&newest), &version_of(&tracking)) == Ordering::Greater,
+        tracking: format!("{}*", prefix),
+        latest: newest,
+        tag: true,
+    })
+}
+
+fn status(available: &Available) -> &'static str {
+    if available.outdated { "outdated" } else { "up to date" }
+}
+
+pub fn installed_label(pkg: &InstalledPackage) -> String {
+    match (&pkg.version, &pkg.commit) {
+        (Some(version), Some(commit)) => format!("{} ({})", version, short(commit)),
+        (None, Some(commit)) => short(commit),
+        (Some(version), None) => version.clone(),
+        (None, None) => "-".to_string(),
+    }
+}
+
+fn short(sha: &str) -> String {
+    sha.chars().take(12).collect()
+}
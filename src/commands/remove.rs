@@ -1,26 +1,109 @@
-use std::env;
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
 use std::process::Command;
 use ansi_term::Colour::{Green, Red, Yellow};
-use crate::utils;
+use crate::utils::{self, InstalledPackage};
 
-fn get_local_bin_path() -> std::path::PathBuf {
-    env::var("HOME")
-        .map(|home| std::path::PathBuf::from(home).join(".local/bin"))
-        .expect("HOME environment variable not set")
+pub fn remove(package: &str, force: bool) {
+    let installed = utils::get_installed_packages();
+
+    let Some(pkg) = installed.iter().find(|p| p.name == package) else {
+        eprintln!("{}: Package '{}' not found", Red.paint("Error"), package);
+        return;
+    };
+
+    if !pkg.required_by.is_empty() {
+        println!("{}: {} is required by: {}", Yellow.paint("Warning"), package, pkg.required_by.join(", "));
+        if !force {
+            print!("~> Remove anyway? [y/N] ");
+            io::stdout().flush().unwrap();
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).unwrap();
+
+            if !input.trim().eq_ignore_ascii_case("y") {
+                println!("{}", Yellow.paint("Removal cancelled"));
+                return;
+            }
+        }
+    }
+
+    if remove_package(package) {
+        println!("{}", Green.paint("~> Removed successfully"));
+
+        let orphans = utils::find_orphans(&utils::get_installed_packages());
+        if !orphans.is_empty() {
+            println!("~> No longer needed: {}", orphans.join(", "));
+            println!("~> Run 'radon autoremove' to remove them");
+        }
+    }
+}
+
+pub fn autoremove(yes: bool) {
+    let orphans = utils::find_orphans(&utils::get_installed_packages());
+    if orphans.is_empty() {
+        println!("No unneeded dependencies to remove");
+        return;
+    }
+
+    println!("~> Packages installed as dependencies and no longer needed:");
+    for name in &orphans {
+        println!("  {}", name);
+    }
+
+    if !yes {
+        print!("~> Remove them? [Y/n] ");
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+
+        if input.trim().eq_ignore_ascii_case("n") {
+            println!("{}", Yellow.paint("Removal cancelled"));
+            return;
+        }
+    }
+
+    let removed = orphans.iter().filter(|name| remove_package(name)).count();
+    println!("{}", Green.paint(format!("~> Removed {} of {} packages", removed, orphans.len())));
 }
 
-pub fn remove(package: &str) {
+fn remove_package(package: &str) -> bool {
     let privilege_cmd = utils::get_privilege_command();
     let mut installed = utils::get_installed_packages();
-    
-    if let Some(index) = installed.iter().position(|p| p.name == package) {
-        let pkg = installed.remove(index);
-        let bin_path = Path::new(&pkg.location);
-        
+
+    let Some(index) = installed.iter().position(|p| p.name == package) else {
+        eprintln!("{}: Package '{}' not found", Red.paint("Error"), package);
+        return false;
+    };
+    let pkg: InstalledPackage = installed.remove(index);
+    let files = pkg.installed_files();
+
+    let prefix = Path::new(&pkg.location)
+        .parent()
+        .and_then(|bin| bin.parent())
+        .unwrap_or(Path::new("/usr/local"))
+        .to_path_buf();
+    let privileged = pkg.location.starts_with("/usr");
+    // The checkout is long gone by now, so remove hooks run in an empty directory
+    // and have to reach installed files through $PREFIX
+    let hook_dir = Path::new("/tmp/radon/hooks").join(&pkg.name);
+    let _ = fs::remove_dir_all(&hook_dir);
+    if let Err(e) = fs::create_dir_all(&hook_dir) {
+        eprintln!("{}: Failed to create {}: {}", Red.paint("Error"), hook_dir.display(), e);
+        return false;
+    }
+
+    if !utils::run_hook("pre_remove", &pkg.pre_remove, &hook_dir, &prefix, &pkg.name, pkg.version.as_deref(), privileged) {
+        eprintln!("{}: Aborting removal of {}", Red.paint("Error"), package);
+        let _ = fs::remove_dir_all(&hook_dir);
+        return false;
+    }
+
+    for file in &files {
+        let bin_path = Path::new(file);
+
         if bin_path.exists() {
-            if pkg.location.starts_with("/usr") {
+            if file.starts_with("/usr") {
                 Command::new(&privilege_cmd)
                     .arg("rm")
                     .arg("-f")
@@ -35,20 +118,13 @@ pub fn remove(package: &str) {
         } else {
             println!("{}: Binary not found at {}", Yellow.paint("Warning"), bin_path.display());
         }
-        
-        let temp_path = Path::new("/tmp").join("radon-installed.yaml");
-        fs::write(&temp_path, serde_yaml::to_string(&installed).unwrap()).unwrap();
-        
-        Command::new(&privilege_cmd)
-            .arg("mv")
-            .arg(&temp_path)
-            .arg("/etc/radon/installed.yaml")
-            .status()
-            .expect("Failed to update package list");
-        
-        println!("{}", Green.paint("~> Removed successfully"));
-    } else {
-        eprintln!("{}: Package '{}' not found", Red.paint("Error"), package);
     }
-}
 
+    utils::save_installed_packages(&installed);
+
+    if !utils::run_hook("post_remove", &pkg.post_remove, &hook_dir, &prefix, &pkg.name, pkg.version.as_deref(), privileged) {
+        eprintln!("{}: post_remove hook failed", Yellow.paint("Warning"));
+    }
+    let _ = fs::remove_dir_all(&hook_dir);
+    true
+}
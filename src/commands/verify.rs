@@ -0,0 +1,82 @@
+use std::path::Path;
+use ansi_term::Colour::{Green, Red, Yellow};
+use crate::utils::{self, InstalledFile, InstalledPackage};
+
+pub fn verify(package: Option<&str>) {
+    let installed = utils::get_installed_packages();
+    let packages: Vec<&InstalledPackage> = match package {
+        Some(name) => match installed.iter().find(|p| p.name == name) {
+            Some(pkg) => vec![pkg],
+            None => {
+                eprintln!("{}: Package '{}' not found", Red.paint("Error"), name);
+                std::process::exit(1);
+            }
+        },
+        None => installed.iter().collect(),
+    };
+
+    // A package that can't be checked fails too, verify is meant for scripts
+    let (mut failed, mut unverified) = (0, 0);
+    for pkg in &packages {
+        let problems = check_package(pkg);
+        if problems.is_empty() {
+            println!("{} {}", pkg.name, Green.paint("OK"));
+            continue;
+        }
+
+        if pkg.file_info.is_empty() {
+            unverified += 1;
+            println!("{} {}", pkg.name, Yellow.paint("UNVERIFIED"));
+        } else {
+            failed += 1;
+            println!("{} {}", pkg.name, Red.paint("FAILED"));
+        }
+        for problem in problems {
+            println!("  {}", problem);
+        }
+    }
+
+    println!("~> Verified {} packages, {} with problems, {} unverified", packages.len(), failed, unverified);
+    if failed + unverified > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn check_package(pkg: &InstalledPackage) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if pkg.file_info.is_empty() {
+        problems.push("unverified: no checksums recorded, reinstall it to enable verification".to_string());
+    }
+    for file in pkg.installed_files().into_iter().filter(|f| !pkg.file_info.contains_key(f)) {
+        if !Path::new(&file).exists() {
+            problems.push(format!("missing: {}", file));
+        } else if !pkg.file_info.is_empty() {
+            problems.push(format!("unverified: {} (no checksum recorded)", file));
+        }
+    }
+
+    for (file, expected) in &pkg.file_info {
+        let path = Path::new(file);
+        if !path.exists() {
+            problems.push(format!("missing: {}", file));
+            continue;
+        }
+        let Some(actual) = InstalledFile::read(path) else {
+            problems.push(format!("unreadable: {}", file));
+            continue;
+        };
+
+        if actual.sha256 != expected.sha256 {
+            problems.push(format!("modified: {}", file));
+        }
+        if actual.mode != expected.mode {
+            problems.push(format!("permissions: {} (expected {}, found {})", file, expected.mode, actual.mode));
+        }
+        if actual.uid != expected.uid || actual.gid != expected.gid {
+            problems.push(format!("owner: {} (expected {}:{}, found {}:{})",
+                file, expected.uid, expected.gid, actual.uid, actual.gid));
+        }
+    }
+    problems
+}
@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::utils::{self, Distro};
+
+pub const KINDS: &[&str] = &["cmd", "pkgconfig", "header", "lib"];
+
+const PACKAGE_NAMES: &[(&str, &[(&str, &str)])] = &[
+    ("g++", &[("apt", "build-essential"), ("dnf", "gcc-c++"), ("zypper", "gcc-c++"), ("pacman", "gcc"), ("xbps", "gcc"), ("emerge", "sys-devel/gcc")]),
+    ("gcc", &[("apt", "build-essential"), ("emerge", "sys-devel/gcc")]),
+    ("cc", &[("apt", "build-essential"), ("dnf", "gcc"), ("zypper", "gcc"), ("pacman", "gcc"), ("xbps", "gcc"), ("apk", "build-base"), ("emerge", "sys-devel/gcc"), ("pkg", "gcc")]),
+    ("make", &[("apt", "build-essential"), ("emerge", "dev-build/make"), ("pkg", "gmake")]),
+    ("pkg-config", &[("dnf", "pkgconf-pkg-config"), ("pacman", "pkgconf"), ("apk", "pkgconf"), ("zypper", "pkgconf-pkg-config"), ("emerge", "dev-util/pkgconf"), ("pkg", "pkgconf")]),
+    ("cargo", &[("pacman", "rust"), ("emerge", "dev-lang/rust"), ("pkg", "rust")]),
+    ("libtoolize", &[("apt", "libtool"), ("dnf", "libtool"), ("zypper", "libtool"), ("pacman", "libtool"), ("xbps", "libtool"), ("apk", "libtool"), ("emerge", "dev-build/libtool"), ("pkg", "libtool")]),
+    ("autoconf", &[("emerge", "dev-build/autoconf")]),
+    ("automake", &[("emerge", "dev-build/automake")]),
+    ("cmake", &[("emerge", "dev-build/cmake")]),
+    ("meson", &[("emerge", "dev-build/meson")]),
+    ("ninja", &[("apt", "ninja-build"), ("dnf", "ninja-build"), ("apk", "samurai"), ("emerge", "dev-build/ninja")]),
+    ("nimble", &[("apt", "nim"), ("dnf", "nim"), ("xbps", "nim"), ("apk", "nim")]),
+    ("cabal", &[("apt", "cabal-install"), ("dnf", "cabal-install"), ("zypper", "cabal-install"), ("pacman", "cabal-install"), ("xbps", "cabal-install"), ("emerge", "dev-haskell/cabal-install"), ("pkg", "hs-cabal-install")]),
+    ("stack", &[("apt", "haskell-stack"), ("pkg", "stack")]),
+    ("dune", &[("apt", "ocaml-dune"), ("dnf", "ocaml-dune"), ("zypper", "ocaml-dune"), ("emerge", "dev-ml/dune"), ("pkg", "ocaml-dune")]),
+    ("openssl", &[("apt", "libssl-dev"), ("dnf", "openssl-devel"), ("zypper", "libopenssl-devel"), ("xbps", "openssl-devel"), ("apk", "openssl-dev"), ("emerge", "dev-libs/openssl")]),
+    ("zlib", &[("apt", "zlib1g-dev"), ("dnf", "zlib-devel"), ("zypper", "zlib-devel"), ("xbps", "zlib-devel"), ("apk", "zlib-dev"), ("emerge", "sys-libs/zlib")]),
+    ("libgit2", &[("apt", "libgit2-dev"), ("dnf", "libgit2-devel"), ("zypper", "libgit2-devel"), ("xbps", "libgit2-devel"), ("apk", "libgit2-dev"), ("emerge", "dev-libs/libgit2")]),
+    ("libcurl", &[("apt", "libcurl4-openssl-dev"), ("dnf", "libcurl-devel"), ("zypper", "libcurl-devel"), ("pacman", "curl"), ("xbps", "libcurl-devel"), ("apk", "curl-dev"), ("emerge", "net-misc/curl"), ("pkg", "curl")]),
+];
+
+const OVERRIDE_FILES: &[&str] = &["/etc/radon/packages.toml"];
+
+#[derive(Clone, PartialEq)]
+pub enum Requirement {
+    Command(String),
+    PkgConfig { name: String, constraint: Option<(String, String)> },
+    Header(String),
+    Library(String),
+}
+
+impl Requirement {
+    pub fn parse(spec: &str) -> Result<Requirement, String> {
+        let (kind, rest) = match spec.split_once(':') {
+            Some((kind, rest)) => (kind, rest.trim()),
+            None => ("cmd", spec.trim()),
+        };
+        if rest.is_empty() {
+            return Err(format!("'{}' is missing a name", spec));
+        }
+
+        match kind {
+            "cmd" => Ok(Requirement::Command(rest.to_string())),
+            "header" => Ok(Requirement::Header(rest.to_string())),
+            "lib" => match rest.strip_prefix("lib").unwrap_or(rest) {
+                "" => Err(format!("'{}' is missing a name", spec)),
+                name => Ok(Requirement::Library(name.to_string())),
+            },
+            "pkgconfig" => {
+                let split = rest.find(['>', '<', '=']);
+                let (name, constraint) = match split {
+                    Some(i) => {
+                        let (name, tail) = rest.split_at(i);
+                        let op_len = tail.chars().take_while(|c| matches!(c, '>' | '<' | '=')).count();
+                        let (op, version) = tail.split_at(op_len);
+                        if !matches!(op, ">=" | "<=" | ">" | "<" | "=" | "==") || version.trim().is_empty() {
+                            return Err(format!("'{}' has an invalid version constraint", spec));
+                        }
+                        (name.trim(), Some((op.to_string(), version.trim().to_string())))
+                    }
+                    None => (rest, None),
+                };
+                if name.is_empty() {
+                    return Err(format!("'{}' is missing a name", spec));
+                }
+                Ok(Requirement::PkgConfig { name: name.to_string(), constraint })
+            }
+            _ => Err(format!("unknown dependency type '{}', expected one of: {}", kind, KINDS.join(", "))),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Requirement::Command(name)
+            | Requirement::Header(name)
+            | Requirement::Library(name)
+            | Requirement::PkgConfig { name, .. } => name,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Requirement::Command(name) => {
+                if command_exists(name) { Ok(()) } else { Err("command not found".to_string()) }
+            }
+            Requirement::PkgConfig { name, constraint } => check_pkgconfig(name, constraint.as_ref()),
+            Requirement::Header(name) => {
+                if include_dirs().iter().any(|d| d.join(name).is_file()) {
+                    Ok(())
+                } else {
+                    Err("header not found".to_string())
+                }
+            }
+            Requirement::Library(name) => {
+                if library_exists(name) { Ok(()) } else { Err("library not found".to_string()) }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Requirement::Command(name) => write!(f, "cmd:{}", name),
+            Requirement::Header(name) => write!(f, "header:{}", name),
+            Requirement::Library(name) => write!(f, "lib:{}", name),
+            Requirement::PkgConfig { name, constraint: None } => write!(f, "pkgconfig:{}", name),
+            Requirement::PkgConfig { name, constraint: Some((op, version)) } => {
+                write!(f, "pkgconfig:{}{}{}", name, op, version)
+            }
+        }
+    }
+}
+
+pub fn linked_libraries(path: &Path) -> Option<Vec<(String, Option<String>)>> {
+    let output = Command::new("ldd")
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let libraries = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (name, target) = line.trim().split_once(" => ")?;
+            let target = target.split(" (").next().unwrap_or("").trim();
+            let resolved = target.starts_with('/').then(|| target.to_string());
+            Some((name.to_string(), resolved))
+        })
+        .collect();
+    Some(libraries)
+}
+
+pub fn command_exists(name: &str) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("command -v {}", name))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+fn check_pkgconfig(name: &str, constraint: Option<&(String, String)>) -> Result<(), String> {
+    if !command_exists("pkg-config") {
+        return Err("pkg-config is not installed".to_string());
+    }
+
+    let output = Command::new("pkg-config")
+        .arg("--modversion")
+        .arg(name)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run pkg-config: {}", e))?;
+
+    if !output.status.success() {
+        return Err("not found by pkg-config".to_string());
+    }
+
+    let found = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let Some((op, wanted)) = constraint else {
+        return Ok(());
+    };
+
+    let ord = utils::compare_versions(&found, wanted);
+    let satisfied = match op.as_str() {
+        ">=" => ord != Ordering::Less,
+        "<=" => ord != Ordering::Greater,
+        ">" => ord == Ordering::Greater,
+        "<" => ord == Ordering::Less,
+        _ => ord == Ordering::Equal,
+    };
+
+    if satisfied {
+        Ok(())
+    } else {
+        Err(format!("found version {}, need {}{}", found, op, wanted))
+    }
+}
+
+fn split_env_paths(var: &str) -> Vec<PathBuf> {
+    env::var(var)
+        .map(|v| v.split(':').filter(|p| !p.is_empty()).map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+fn include_dirs() -> Vec<PathBuf> {
+    let mut dirs = split_env_paths("CPATH");
+    dirs.extend(split_env_paths("C_INCLUDE_PATH"));
+    dirs.extend(["/usr/local/include", "/usr/include"].map(PathBuf::from));
+    dirs.push(Path::new("/usr/include").join(format!("{}-linux-gnu", env::consts::ARCH)));
+    dirs
+}
+
+fn library_dirs() -> Vec<PathBuf> {
+    let mut dirs = split_env_paths("LIBRARY_PATH");
+    dirs.extend(split_env_paths("LD_LIBRARY_PATH"));
+    dirs.extend(["/usr/local/lib", "/usr/local/lib64", "/usr/lib", "/usr/lib64", "/lib", "/lib64"].map(PathBuf::from));
+    let triplet = format!("{}-linux-gnu", env::consts::ARCH);
+    dirs.push(Path::new("/usr/lib").join(&triplet));
+    dirs.push(Path::new("/lib").join(&triplet));
+    dirs
+}
+
+fn library_exists(name: &str) -> bool {
+    let prefix = format!("lib{}.", name);
+
+    if let Ok(output) = Command::new("ldconfig").arg("-p").stderr(Stdio::null()).output()
+        && String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|l| l.trim_start().starts_with(&format!("{}so", prefix)))
+    {
+        return true;
+    }
+
+    library_dirs().iter().any(|dir| {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries.filter_map(|e| e.ok()).any(|e| {
+                    let file = e.file_name().to_string_lossy().to_string();
+                    file.starts_with(&prefix) && (file.contains(".so") || file.ends_with(".a"))
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn load_overrides() -> HashMap<String, HashMap<String, String>> {
+    let mut files: Vec<PathBuf> = OVERRIDE_FILES.iter().map(PathBuf::from).collect();
+    if let Ok(home) = env::var("HOME") {
+        files.push(Path::new(&home).join(".config/radon/packages.toml"));
+    }
+
+    let mut overrides: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for file in files {
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let Ok(table) = content.parse::<toml::Table>() else {
+            eprintln!("{}: Ignoring invalid {}", ansi_term::Colour::Yellow.paint("Warning"), file.display());
+            continue;
+        };
+        for (manager, names) in table {
+            let Some(names) = names.as_table() else {
+                continue;
+            };
+            let entry = overrides.entry(manager).or_default();
+            for (dep, package) in names {
+                if let Some(package) = package.as_str() {
+                    entry.insert(dep.clone(), package.to_string());
+                }
+            }
+        }
+    }
+    overrides
+}
+
+// Only commands fall back to their own name, other kinds rarely match a
+// package name and would hand the package manager something like zlib.h
+pub fn distro_packages(req: &Requirement, distro: Option<&Distro>) -> Option<Vec<String>> {
+    let fallback = matches!(req, Requirement::Command(_)).then(|| req.name().to_string());
+    let Some(distro) = distro else {
+        return fallback.map(|name| vec![name]);
+    };
+
+    let keys = [req.to_string(), req.name().to_string()];
+    let overrides = load_overrides();
+    let from_overrides = overrides.get(distro.manager)
+        .or_else(|| overrides.get(distro.name))
+        .and_then(|names| keys.iter().find_map(|k| names.get(k)))
+        .cloned();
+
+    let package = from_overrides.or_else(|| {
+        keys.iter().find_map(|k| {
+            PACKAGE_NAMES.iter()
+                .find(|(dep, _)| dep == k)
+                .and_then(|(_, names)| names.iter().find(|(manager, _)| *manager == distro.manager))
+                .map(|(_, package)| package.to_string())
+        })
+    });
+
+    let package = package.or(fallback)?;
+    Some(package.split_whitespace().map(|p| p.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(spec: &str) -> String {
+        Requirement::parse(spec).unwrap_or_else(|e| panic!("{}", e)).to_string()
+    }
+
+    #[test]
+    fn untyped_requirements_are_commands() {
+        assert!(Requirement::parse("git") == Ok(Requirement::Command("git".to_string())));
+        assert_eq!(parsed(" cmake "), "cmd:cmake");
+    }
+
+    #[test]
+    fn typed_requirements_keep_their_kind() {
+        assert_eq!(parsed("header:zlib.h"), "header:zlib.h");
+        assert_eq!(parsed("lib:libssl"), "lib:ssl");
+        assert_eq!(parsed("lib:ssl"), "lib:ssl");
+        assert_eq!(parsed("lib:liblibrary"), "lib:library");
+        assert_eq!(parsed("pkgconfig:openssl"), "pkgconfig:openssl");
+    }
+
+    #[test]
+    fn pkgconfig_version_constraints() {
+        let expected = Requirement::PkgConfig {
+            name: "openssl".to_string(),
+            constraint: Some((">=".to_string(), "1.1".to_string())),
+        };
+        assert!(Requirement::parse("pkgconfig:openssl>=1.1") == Ok(expected.clone()));
+        assert!(Requirement::parse("pkgconfig:openssl >= 1.1") == Ok(expected));
+        assert_eq!(parsed("pkgconfig:gtk+-3.0=3.24"), "pkgconfig:gtk+-3.0=3.24");
+        assert_eq!(parsed("pkgconfig:glib-2.0<2.80"), "pkgconfig:glib-2.0<2.80");
+    }
+
+    #[test]
+    fn invalid_requirements_are_rejected() {
+        for spec in ["pkgconfig:openssl=>1.1", "pkgconfig:openssl>=", "pkgconfig:>=1.1", "header:", "lib:lib", "rpm:zlib"] {
+            assert!(Requirement::parse(spec).is_err(), "{}", spec);
+        }
+        let err = Requirement::parse("rpm:zlib").err().unwrap();
+        assert!(err.starts_with("unknown dependency type 'rpm'"));
+    }
+
+    #[test]
+    fn only_commands_and_mapped_requirements_get_packages() {
+        let apt = utils::DISTROS.iter().find(|d| d.manager == "apt");
+        let packages = |spec: &str, distro| distro_packages(&Requirement::parse(spec).unwrap(), distro);
+        assert_eq!(packages("pkgconfig:zlib", apt), Some(vec!["zlib1g-dev".to_string()]));
+        assert_eq!(packages("cmd:somecmd", apt), Some(vec!["somecmd".to_string()]));
+        assert_eq!(packages("cmd:somecmd", None), Some(vec!["somecmd".to_string()]));
+        assert_eq!(packages("header:zlib.h", apt), None);
+        assert_eq!(packages("pkgconfig:gtk+-3.0", apt), None);
+        assert_eq!(packages("pkgconfig:zlib", None), None);
+    }
+}
@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::Path;
+use toml::Table;
+
+const CMAKE_PACKAGES: &[(&str, &str)] = &[
+    ("openssl", "pkgconfig:openssl"),
+    ("zlib", "pkgconfig:zlib"),
+    ("curl", "pkgconfig:libcurl"),
+    ("png", "pkgconfig:libpng"),
+    ("jpeg", "pkgconfig:libjpeg"),
+    ("sqlite3", "pkgconfig:sqlite3"),
+    ("libxml2", "pkgconfig:libxml-2.0"),
+    ("freetype", "pkgconfig:freetype2"),
+    ("x11", "pkgconfig:x11"),
+    ("sdl2", "pkgconfig:sdl2"),
+    ("libgit2", "pkgconfig:libgit2"),
+    ("pkgconfig", "cmd:pkg-config"),
+    ("boost", "header:boost/version.hpp"),
+    ("qt5", "pkgconfig:Qt5Core"),
+    ("qt6", "pkgconfig:Qt6Core"),
+    ("llvm", "cmd:llvm-config"),
+    ("gtest", "pkgconfig:gtest"),
+    ("fmt", "pkgconfig:fmt"),
+    ("glfw3", "pkgconfig:glfw3"),
+    ("lua", "pkgconfig:lua"),
+];
+
+const SYS_CRATES: &[(&str, &str)] = &[
+    ("openssl-sys", "pkgconfig:openssl"),
+    ("libgit2-sys", "pkgconfig:libgit2"),
+    ("libz-sys", "pkgconfig:zlib"),
+    ("curl-sys", "pkgconfig:libcurl"),
+    ("libsqlite3-sys", "pkgconfig:sqlite3"),
+    ("libssh2-sys", "pkgconfig:libssh2"),
+    ("libdbus-sys", "pkgconfig:dbus-1"),
+    ("alsa-sys", "pkgconfig:alsa"),
+    ("freetype-sys", "pkgconfig:freetype2"),
+    ("fontconfig-sys", "pkgconfig:fontconfig"),
+    ("gtk-sys", "pkgconfig:gtk+-3.0"),
+    ("glib-sys", "pkgconfig:glib-2.0"),
+    ("x11-sys", "pkgconfig:x11"),
+    ("libudev-sys", "pkgconfig:libudev"),
+];
+
+pub fn infer_system_deps(build_dir: &Path, build_system: &str) -> Vec<String> {
+    let read = |file: &str| fs::read_to_string(build_dir.join(file)).unwrap_or_default();
+
+    let mut deps = match build_system {
+        "cmake" => infer_cmake(&read("CMakeLists.txt")),
+        "meson" => infer_meson(&read("meson.build")),
+        "autotools" => {
+            let script = ["configure.ac", "configure.in"]
+                .into_iter()
+                .find(|f| build_dir.join(f).exists())
+                .unwrap_or("configure.ac");
+            infer_autoconf(&read(script))
+        }
+        "cargo" => infer_cargo(&read("Cargo.toml")),
+        _ => Vec::new(),
+    };
+
+    let mut seen = Vec::new();
+    deps.retain(|d| {
+        let keep = !seen.contains(d);
+        seen.push(d.clone());
+        keep
+    });
+    deps
+}
+
+fn call_args<'a>(content: &'a str, func: &str, ignore_case: bool) -> Vec<&'a str> {
+    let haystack = if ignore_case { content.to_ascii_lowercase() } else { content.to_string() };
+    let needle = format!("{}(", if ignore_case { func.to_ascii_lowercase() } else { func.to_string() });
+
+    let mut calls = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = haystack[from..].find(&needle) {
+        let start = from + pos;
+        let args_start = start + needle.len();
+        from = args_start;
+
+        let preceded_by_ident = haystack[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+        if preceded_by_ident {
+            continue;
+        }
+
+        let mut depth = 1;
+        let mut end = None;
+        for (i, c) in content[args_start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(args_start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(end) = end {
+            calls.push(&content[args_start..end]);
+            from = end;
+        }
+    }
+    calls
+}
+
+fn parse_module_list(tokens: &[&str]) -> Vec<String> {
+    let mut modules: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let is_op = |t: &str| matches!(t, ">=" | "<=" | ">" | "<" | "=" | "==");
+        if is_op(token) {
+            if let (Some(last), Some(version)) = (modules.last_mut(), tokens.get(i + 1)) {
+                last.push_str(token);
+                last.push_str(version);
+            }
+            i += 2;
+            continue;
+        }
+        modules.push(format!("pkgconfig:{}", token));
+        i += 1;
+    }
+    modules
+}
+
+fn infer_cmake(content: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+
+    // CMake package names rarely match a pkg-config module, so only known ones are reported
+    for args in call_args(content, "find_package", true) {
+        let Some(name) = args.split_whitespace().next() else {
+            continue;
+        };
+        let lower = name.to_ascii_lowercase();
+        if let Some((_, req)) = CMAKE_PACKAGES.iter().find(|(pkg, _)| *pkg == lower) {
+            deps.push(req.to_string());
+        }
+    }
+
+    let keywords = ["REQUIRED", "QUIET", "IMPORTED_TARGET", "GLOBAL", "NO_CMAKE_PATH", "NO_CMAKE_ENVIRONMENT_PATH"];
+    let mut uses_pkg_config = false;
+    for (func, first_only) in [("pkg_check_modules", false), ("pkg_search_module", true)] {
+        for args in call_args(content, func, true) {
+            uses_pkg_config = true;
+            let tokens: Vec<&str> = args.split_whitespace()
+                .skip(1)
+                .filter(|t| !keywords.contains(t) && !t.contains('$'))
+                .collect();
+            let modules = parse_module_list(&tokens);
+            if first_only {
+                deps.extend(modules.into_iter().take(1));
+            } else {
+                deps.extend(modules);
+            }
+        }
+    }
+
+    if uses_pkg_config {
+        deps.push("cmd:pkg-config".to_string());
+    }
+    deps
+}
+
+fn quoted_strings(text: &str) -> Vec<&str> {
+    text.split('\'').skip(1).step_by(2).collect()
+}
+
+fn infer_meson(content: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+    for args in call_args(content, "dependency", false) {
+        let Some(name) = quoted_strings(args).into_iter().next() else {
+            continue;
+        };
+        let compact: String = args.chars().filter(|c| !c.is_whitespace()).collect();
+        if name == "threads" || compact.contains("required:false") {
+            continue;
+        }
+
+        let version = args.find("version")
+            .map(|i| &args[i..])
+            .and_then(|rest| quoted_strings(rest).into_iter().next())
+            .map(|v| v.replace(' ', ""))
+            .unwrap_or_default();
+        deps.push(format!("pkgconfig:{}{}", name, version));
+    }
+    deps
+}
+
+fn split_m4_args(args: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in args.chars() {
+        match c {
+            '[' => {
+                if depth > 0 {
+                    current.push(c);
+                }
+                depth += 1;
+            }
+            ']' => {
+                depth -= 1;
+                if depth > 0 {
+                    current.push(c);
+                }
+            }
+            ',' if depth == 0 => parts.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+fn infer_autoconf(content: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+    for args in call_args(content, "PKG_CHECK_MODULES", false) {
+        let parts = split_m4_args(args);
+        if let Some(modules) = parts.get(1) {
+            let tokens: Vec<&str> = modules.split_whitespace().filter(|t| !t.contains('$')).collect();
+            deps.extend(parse_module_list(&tokens));
+        }
+    }
+    deps
+}
+
+fn infer_cargo(content: &str) -> Vec<String> {
+    let Ok(cargo) = content.parse::<Table>() else {
+        return Vec::new();
+    };
+
+    let mut deps = Vec::new();
+    for section in ["dependencies", "build-dependencies"] {
+        let Some(table) = cargo.get(section).and_then(|d| d.as_table()) else {
+            continue;
+        };
+        // Many -sys crates bundle their library or use another .pc name,
+        // so only known ones are reported
+        for (name, spec) in table {
+            let crate_name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(name);
+            let optional = spec.get("optional").and_then(|o| o.as_bool()).unwrap_or(false);
+            if optional {
+                continue;
+            }
+            if let Some((_, req)) = SYS_CRATES.iter().find(|(krate, _)| *krate == crate_name) {
+                deps.push(req.to_string());
+            }
+        }
+    }
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_args_handles_nesting_and_identifiers() {
+        let content = "x = my_dependency('a')\ny = dependency('b', version : f('c'))";
+        assert_eq!(call_args(content, "dependency", false), ["'b', version : f('c')"]);
+        assert_eq!(call_args("FIND_PACKAGE(ZLIB)", "find_package", true), ["ZLIB"]);
+    }
+
+    #[test]
+    fn cmake_maps_known_packages_only() {
+        let content = "find_package(OpenSSL REQUIRED)\nfind_package(Qt5 COMPONENTS Widgets)\n\
+            find_package(Threads)\nfind_package(SomethingInternal)";
+        assert_eq!(infer_cmake(content), ["pkgconfig:openssl", "pkgconfig:Qt5Core"]);
+    }
+
+    #[test]
+    fn cmake_pkg_check_modules_keeps_versions() {
+        let content = "pkg_check_modules(GTK REQUIRED IMPORTED_TARGET gtk+-3.0>=3.22 glib-2.0 >= 2.50)\n\
+            pkg_search_module(SSL openssl libssl)";
+        assert_eq!(infer_cmake(content), [
+            "pkgconfig:gtk+-3.0>=3.22",
+            "pkgconfig:glib-2.0>=2.50",
+            "pkgconfig:openssl",
+            "cmd:pkg-config",
+        ]);
+    }
+
+    #[test]
+    fn meson_dependencies_skip_optional_and_threads() {
+        let content = "a = dependency('libcurl', version : '>= 7.0')\n\
+            t = dependency('threads')\n\
+            o = dependency('foo', required: false)\n\
+            z = dependency('zlib')";
+        assert_eq!(infer_meson(content), ["pkgconfig:libcurl>=7.0", "pkgconfig:zlib"]);
+    }
+
+    #[test]
+    fn m4_arguments_respect_quoting() {
+        assert_eq!(split_m4_args("[XML], [libxml-2.0 >= 2.9 zlib], [ok], [AC_MSG_ERROR([no, really])]"),
+            ["XML", "libxml-2.0 >= 2.9 zlib", "ok", "AC_MSG_ERROR([no, really])"]);
+        assert_eq!(infer_autoconf("PKG_CHECK_MODULES([XML], [libxml-2.0 >= 2.9 zlib])"),
+            ["pkgconfig:libxml-2.0>=2.9", "pkgconfig:zlib"]);
+    }
+
+    #[test]
+    fn cargo_sys_crates_map_to_libraries() {
+        let content = r#"
+            [dependencies]
+            openssl-sys = "0.9"
+            windows-sys = "0.52"
+            zstd-sys = "2"
+            curl-sys = { version = "0.4", optional = true }
+            z = { package = "libz-sys", version = "1" }
+        "#;
+        assert_eq!(infer_cargo(content), ["pkgconfig:openssl", "pkgconfig:zlib"]);
+    }
+}
@@ -1,10 +1,14 @@
 mod cli;
 mod utils;
+mod manifest;
+mod deps;
+mod infer;
+mod resolve;
 mod commands;
 
 use clap::Parser;
 use cli::{Cli, Commands};
-use commands::{install, remove, search, list, upgrade};
+use commands::{install, remove, search, list, info, files, verify, doctor, outdated, upgrade, lint};
 use commands::convert::convert;
 use std::path::Path;
 
@@ -12,7 +16,7 @@ fn main() {
     utils::setup_radon_dirs();
     let cli = Cli::parse();
     match cli.command {
-        Commands::Install { packages, gitlab, codeberg, local, branch, patches, flags, yes } => {
+        Commands::Install { packages, gitlab, codeberg, local, branch, patches, flags, with, without, yes } => {
             install::install(
                 &packages, 
                 gitlab, 
@@ -21,14 +25,33 @@ fn main() {
                 branch.as_deref(), 
                 patches.as_deref(), 
                 &flags,
+                &with,
+                &without,
                 yes
             );
         },
-        Commands::Remove { package } => remove::remove(&package),
+        Commands::Remove { package, force } => remove::remove(&package, force),
+        Commands::Autoremove { yes } => remove::autoremove(yes),
         Commands::Search { query } => search::search(&query),
-        Commands::List => list::list(),
+        Commands::List { columns, source, build_system, outdated, sort, reverse } => {
+            list::list(&list::ListOptions {
+                columns: &columns,
+                source: source.as_deref(),
+                build_system: build_system.as_deref(),
+                outdated,
+                sort: sort.as_deref(),
+                reverse,
+            });
+        },
+        Commands::Info { package, remote } => info::info(&package, remote),
+        Commands::Files { package } => files::files(&package),
+        Commands::Owns { path } => files::owns(&path),
+        Commands::Verify { package } => verify::verify(package.as_deref()),
+        Commands::Doctor { fix } => doctor::doctor(fix),
+        Commands::Outdated => outdated::outdated(),
         Commands::Upgrade { package, branch, yes } => 
             upgrade::upgrade(package.as_deref(), branch.as_deref(), yes),
         Commands::Convert { file } => convert(file.as_deref().map(Path::new)),
+        Commands::Lint { path, schema, migrate } => lint::lint(path.as_deref(), schema, migrate),
     }
 }
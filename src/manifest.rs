@@ -0,0 +1,942 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use crate::deps::Requirement;
+use crate::resolve::PackageRef;
+use crate::utils::{self, Platform};
+
+pub const SCHEMA_VERSION: u64 = 2;
+
+pub const MANIFEST_FILES: &[&str] = &["radon.json", "radon.toml", "radon.yaml", "radon.yml"];
+
+pub const BUILD_SYSTEMS: &[&str] = &[
+    "make", "autotools", "cargo", "cmake", "meson", "ninja",
+    "nimble", "stack", "cabal", "dune", "custom",
+];
+
+// Build systems that option_flags knows how to pass options to
+const OPTION_BUILD_SYSTEMS: &[&str] = &["make", "autotools", "cargo", "cmake", "meson", "custom"];
+
+// Values a target key part can match, see target_matches
+const TARGET_ARCHES: &[&str] = &[
+    "x86", "x86_64", "arm", "aarch64", "riscv64", "powerpc", "powerpc64",
+    "mips", "mips64", "s390x", "sparc64", "loongarch64",
+];
+const TARGET_OSES: &[&str] = &["linux", "macos", "freebsd", "netbsd", "openbsd", "dragonfly"];
+const TARGET_LIBCS: &[&str] = &["gnu", "glibc", "musl"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Manifest {
+    pub schema: Option<u64>,
+    pub radon_version: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(default = "default_build_system")]
+    pub build_system: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    #[serde(default)]
+    pub build: Vec<BuildStep>,
+    #[serde(default)]
+    pub install: Vec<InstallMapping>,
+    #[serde(default)]
+    pub binaries: BTreeMap<String, String>,
+    #[serde(default)]
+    pub man: Vec<String>,
+    #[serde(default)]
+    pub completions: Completions,
+    #[serde(default)]
+    pub desktop: Vec<String>,
+    #[serde(default)]
+    pub data: BTreeMap<String, String>,
+    #[serde(default)]
+    pub target: BTreeMap<String, Conditional>,
+    #[serde(default)]
+    pub distro: BTreeMap<String, Conditional>,
+    #[serde(default)]
+    pub options: BTreeMap<String, BuildOption>,
+    #[serde(default)]
+    pub pre_install: Vec<String>,
+    #[serde(default)]
+    pub post_install: Vec<String>,
+    #[serde(default)]
+    pub pre_remove: Vec<String>,
+    #[serde(default)]
+    pub post_remove: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BuildOption {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default: bool,
+    pub flag: Option<String>,
+    pub style: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Conditional {
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BuildStep {
+    pub run: String,
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstallMapping {
+    pub from: String,
+    pub to: String,
+    pub mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Completions {
+    pub bash: Option<String>,
+    pub zsh: Option<String>,
+    pub fish: Option<String>,
+}
+
+pub struct ManifestError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Manifest {
+            schema: Some(SCHEMA_VERSION),
+            radon_version: None,
+            name: None,
+            version: None,
+            build_system: default_build_system(),
+            dependencies: Vec::new(),
+            flags: Vec::new(),
+            build: Vec::new(),
+            install: Vec::new(),
+            binaries: BTreeMap::new(),
+            man: Vec::new(),
+            completions: Completions::default(),
+            desktop: Vec::new(),
+            data: BTreeMap::new(),
+            target: BTreeMap::new(),
+            distro: BTreeMap::new(),
+            options: BTreeMap::new(),
+            pre_install: Vec::new(),
+            post_install: Vec::new(),
+            pre_remove: Vec::new(),
+            post_remove: Vec::new(),
+        }
+    }
+}
+
+fn default_build_system() -> String {
+    "make".to_string()
+}
+
+fn error(path: &str, message: impl Into<String>) -> ManifestError {
+    ManifestError { path: path.to_string(), message: message.into() }
+}
+
+pub fn schema() -> Value {
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+    let conditional = json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "dependencies": string_list,
+            "flags": string_list
+        }
+    });
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "radon.json",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "$schema": { "type": "string" },
+            "schema": { "type": "integer", "minimum": 1, "maximum": SCHEMA_VERSION },
+            "radon_version": { "type": "string" },
+            "name": { "type": "string" },
+            "version": { "type": "string" },
+            "build_system": { "type": "string", "enum": BUILD_SYSTEMS },
+            "dependencies": string_list,
+            "flags": string_list,
+            "build": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["run"],
+                    "properties": {
+                        "run": { "type": "string" },
+                        "dir": { "type": "string" },
+                        "env": { "type": "object", "additionalProperties": { "type": "string" } }
+                    }
+                }
+            },
+            "install": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["from", "to"],
+                    "properties": {
+                        "from": { "type": "string" },
+                        "to": { "type": "string" },
+                        "mode": { "type": "string", "pattern": "^[0-7]{3,4}$" }
+                    }
+                }
+            },
+            "binaries": { "type": "object", "additionalProperties": { "type": "string" } },
+            "man": string_list,
+            "completions": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "bash": { "type": "string" },
+                    "zsh": { "type": "string" },
+                    "fish": { "type": "string" }
+                }
+            },
+            "desktop": string_list,
+            "data": { "type": "object", "additionalProperties": { "type": "string" } },
+            "target": { "type": "object", "additionalProperties": conditional },
+            "distro": { "type": "object", "additionalProperties": conditional },
+            "options": {
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "description": { "type": "string" },
+                        "default": { "type": "boolean" },
+                        "flag": { "type": "string" },
+                        "style": { "type": "string", "enum": ["enable", "with"] }
+                    }
+                }
+            },
+            "pre_install": string_list,
+            "post_install": string_list,
+            "pre_remove": string_list,
+            "post_remove": string_list
+        }
+    })
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') {
+        format!("{}[{:?}]", path, key)
+    } else if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn check_schema(schema: &Value, value: &Value, path: &str, errors: &mut Vec<ManifestError>) {
+    if let Some(expected) = schema["type"].as_str() {
+        let found = type_name(value);
+        if found != expected && !(expected == "number" && found == "integer") {
+            errors.push(error(path, format!("expected {}, found {}", expected, found)));
+            return;
+        }
+    }
+
+    if let Some(options) = schema["enum"].as_array() && !options.contains(value) {
+        let names: Vec<&str> = options.iter().filter_map(|o| o.as_str()).collect();
+        errors.push(error(path, format!("must be one of: {}", names.join(", "))));
+    }
+
+    if let Value::Object(map) = value {
+        let properties = schema["properties"].as_object();
+        for (key, field) in map {
+            let field_path = join_path(path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(field_schema) => check_schema(field_schema, field, &field_path, errors),
+                None => match &schema["additionalProperties"] {
+                    Value::Bool(false) => errors.push(error(&field_path, "unknown field")),
+                    extra @ Value::Object(_) => check_schema(extra, field, &field_path, errors),
+                    _ => {}
+                },
+            }
+        }
+        for key in schema["required"].as_array().into_iter().flatten().filter_map(|k| k.as_str()) {
+            if !map.contains_key(key) {
+                errors.push(error(&join_path(path, key), "missing required field"));
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check_schema(item_schema, item, &format!("{}[{}]", path, i), errors);
+        }
+    }
+}
+
+fn is_relative_inside(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn check_semantics(manifest: &Manifest, errors: &mut Vec<ManifestError>) {
+    if manifest.schema == Some(0) {
+        errors.push(error("schema", "schema versions start at 1"));
+    }
+    if manifest.build_system == "custom" && manifest.install.is_empty() && manifest.binaries.is_empty() {
+        errors.push(error("install", "a custom build_system needs at least one install or binaries entry"));
+    }
+    if manifest.build_system != "custom" && !manifest.build.is_empty() {
+        errors.push(error("build", "build steps are only used with the custom build_system"));
+    }
+
+    for (i, step) in manifest.build.iter().enumerate() {
+        if step.run.trim().is_empty() {
+            errors.push(error(&format!("build[{}].run", i), "must not be empty"));
+        }
+        if let Some(dir) = &step.dir && !is_relative_inside(dir) {
+            errors.push(error(&format!("build[{}].dir", i), "must be a relative path inside the repository"));
+        }
+    }
+
+    for (i, mapping) in manifest.install.iter().enumerate() {
+        if !is_relative_inside(&mapping.from) {
+            errors.push(error(&format!("install[{}].from", i), "must be a relative path inside the repository"));
+        }
+        if !is_relative_inside(&mapping.to) {
+            errors.push(error(&format!("install[{}].to", i), "must be a relative path inside the install prefix"));
+        }
+        if let Some(mode) = &mapping.mode {
+            let valid = (3..=4).contains(&mode.len()) && mode.chars().all(|c| ('0'..='7').contains(&c));
+            if !valid {
+                errors.push(error(&format!("install[{}].mode", i), "must be an octal mode such as 755"));
+            }
+        }
+    }
+
+    for (from, name) in &manifest.binaries {
+        let path = join_path("binaries", from);
+        if !is_relative_inside(from) {
+            errors.push(error(&path, "must be a relative path inside the repository"));
+        }
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            errors.push(error(&path, "installed name must be a plain file name"));
+        }
+    }
+
+    for (i, page) in manifest.man.iter().enumerate() {
+        let path = format!("man[{}]", i);
+        if !is_relative_inside(page) {
+            errors.push(error(&path, "must be a relative path inside the repository"));
+        } else if man_section(page).is_none() {
+            errors.push(error(&path, "file name must end in a man section such as .1"));
+        }
+    }
+
+    let completions = [
+        ("bash", &manifest.completions.bash),
+        ("zsh", &manifest.completions.zsh),
+        ("fish", &manifest.completions.fish),
+    ];
+    for (shell, file) in completions {
+        if let Some(file) = file && !is_relative_inside(file) {
+            errors.push(error(&format!("completions.{}", shell), "must be a relative path inside the repository"));
+        }
+    }
+
+    for (i, file) in manifest.desktop.iter().enumerate() {
+        if !is_relative_inside(file) {
+            errors.push(error(&format!("desktop[{}]", i), "must be a relative path inside the repository"));
+        }
+    }
+
+    let conditional = manifest.target.iter().map(|(k, c)| (join_path("target", k), c))
+        .chain(manifest.distro.iter().map(|(k, c)| (join_path("distro", k), c)));
+    let dependency_lists = std::iter::once(("dependencies".to_string(), &manifest.dependencies))
+        .chain(conditional.map(|(path, c)| (format!("{}.dependencies", path), &c.dependencies)));
+    for (path, deps) in dependency_lists {
+        for (i, dep) in deps.iter().enumerate() {
+            if PackageRef::is_package_spec(dep) {
+                if PackageRef::parse(dep).is_none() {
+                    errors.push(error(&format!("{}[{}]", path, i), "expected a package reference like github:owner/repo[@ref]"));
+                }
+            } else if let Err(reason) = Requirement::parse(dep) {
+                errors.push(error(&format!("{}[{}]", path, i), reason));
+            }
+        }
+    }
+
+    if !manifest.options.is_empty() && !OPTION_BUILD_SYSTEMS.contains(&manifest.build_system.as_str()) {
+        errors.push(error("options", format!(
+            "options can't be passed to the {} build_system, supported: {}",
+            manifest.build_system, OPTION_BUILD_SYSTEMS.join(", "),
+        )));
+    }
+
+    for (name, option) in &manifest.options {
+        let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid(name) {
+            errors.push(error(&join_path("options", name), "option names may only contain letters, digits, '_' and '-'"));
+        }
+        if let Some(flag) = &option.flag && !valid(flag) {
+            errors.push(error(&format!("{}.flag", join_path("options", name)), "may only contain letters, digits, '_' and '-'"));
+        }
+    }
+
+    for (hook, commands) in manifest.hooks() {
+        for (i, command) in commands.iter().enumerate() {
+            if command.trim().is_empty() {
+                errors.push(error(&format!("{}[{}]", hook, i), "must not be empty"));
+            }
+        }
+    }
+
+    for key in manifest.target.keys() {
+        for part in key.split('-') {
+            if ![TARGET_ARCHES, TARGET_OSES, TARGET_LIBCS].iter().any(|known| known.contains(&part)) {
+                errors.push(error(&join_path("target", key), format!(
+                    "unknown target '{}', expected '-'-separated values from: {}, {}, {}",
+                    part, TARGET_ARCHES.join(", "), TARGET_OSES.join(", "), TARGET_LIBCS.join(", "),
+                )));
+                break;
+            }
+        }
+    }
+
+    for key in manifest.distro.keys() {
+        if !utils::DISTROS.iter().any(|d| key == d.name || key == d.manager) {
+            let known: Vec<&str> = utils::DISTROS.iter().map(|d| d.name).collect();
+            errors.push(error(&join_path("distro", key), format!("unknown distro, expected one of: {}", known.join(", "))));
+        }
+    }
+
+    for (from, to) in &manifest.data {
+        let path = join_path("data", from);
+        if !is_relative_inside(from) {
+            errors.push(error(&path, "must be a relative path inside the repository"));
+        }
+        if !is_relative_inside(to) {
+            errors.push(error(&path, "destination must be a relative path inside share/"));
+        }
+    }
+}
+
+impl Manifest {
+    pub fn hooks(&self) -> [(&'static str, &[String]); 4] {
+        [
+            ("pre_install", &self.pre_install),
+            ("post_install", &self.post_install),
+            ("pre_remove", &self.pre_remove),
+            ("post_remove", &self.post_remove),
+        ]
+    }
+
+    pub fn resolve_options(&self, with: &[String], without: &[String]) -> Result<Vec<String>, String> {
+        for name in with.iter().chain(without) {
+            if !self.options.contains_key(name) {
+                let known: Vec<&str> = self.options.keys().map(|k| k.as_str()).collect();
+                return Err(if known.is_empty() {
+                    format!("Unknown option '{}', this package declares no options", name)
+                } else {
+                    format!("Unknown option '{}', expected one of: {}", name, known.join(", "))
+                });
+            }
+        }
+        if let Some(name) = with.iter().find(|n| without.contains(n)) {
+            return Err(format!("Option '{}' is both enabled and disabled", name));
+        }
+
+        Ok(self.options.iter()
+            .filter(|(name, option)| with.contains(name) || (option.default && !without.contains(name)))
+            .map(|(name, _)| name.clone())
+            .collect())
+    }
+
+    pub fn option_flags(&self, build_system: &str, enabled: &[String], default_features: &[String]) -> Vec<String> {
+        if build_system == "cargo" {
+            // Turning off a feature from Cargo.toml's default list needs --no-default-features,
+            // with the remaining defaults passed back in explicitly
+
+            let feature = |(name, option): (&String, &BuildOption)| option.flag.clone().unwrap_or_else(|| name.clone());
+            let mut features: Vec<String> = self.options.iter()
+                .filter(|(name, _)| enabled.contains(name))
+                .map(feature)
+                .collect();
+            let disabled: Vec<String> = self.options.iter()
+                .filter(|(name, _)| !enabled.contains(name))
+                .map(feature)
+                .filter(|f| default_features.contains(f))
+                .collect();
+
+            let mut flags = Vec::new();
+            if !disabled.is_empty() {
+                flags.push("--no-default-features".to_string());
+                for f in default_features {
+                    if !disabled.contains(f) && !features.contains(f) {
+                        features.push(f.clone());
+                    }
+                }
+            }
+            if !features.is_empty() {
+                flags.push("--features".to_string());
+                flags.push(features.join(","));
+            }
+            return flags;
+        }
+
+        self.options.iter()
+            .filter_map(|(name, option)| {
+                let on = enabled.contains(name);
+                let flag = option.flag.clone().unwrap_or_else(|| name.clone());
+                match build_system {
+                    "cmake" => Some(format!("-D{}={}", flag, if on { "ON" } else { "OFF" })),
+                    "meson" => Some(format!("-D{}={}", flag, on)),
+                    "autotools" => {
+                        let (yes, no) = match option.style.as_deref() {
+                            Some("with") => ("with", "without"),
+                            _ => ("enable", "disable"),
+                        };
+                        Some(format!("--{}-{}", if on { yes } else { no }, flag))
+                    }
+                    "make" | "custom" => Some(format!("{}={}", flag, if on { 1 } else { 0 })),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    pub fn package_dependencies(&self) -> Vec<PackageRef> {
+        self.dependencies.iter()
+            .filter_map(|d| PackageRef::parse(d))
+            .collect()
+    }
+
+    pub fn system_dependencies(&self) -> Vec<String> {
+        self.dependencies.iter()
+            .filter(|d| !PackageRef::is_package_spec(d))
+            .cloned()
+            .collect()
+    }
+
+    pub fn apply_platform(&mut self, platform: &Platform) -> Vec<String> {
+        let mut matched = Vec::new();
+        let mut sections: Vec<(String, Conditional)> = Vec::new();
+
+        for (key, section) in &self.target {
+            if target_matches(key, platform) {
+                sections.push((format!("target.{}", key), section.clone()));
+            }
+        }
+        for (key, section) in &self.distro {
+            if distro_matches(key, platform) {
+                sections.push((format!("distro.{}", key), section.clone()));
+            }
+        }
+
+        for (label, section) in sections {
+            for dep in section.dependencies {
+                if !self.dependencies.contains(&dep) {
+                    self.dependencies.push(dep);
+                }
+            }
+            self.flags.extend(section.flags);
+            matched.push(label);
+        }
+        matched
+    }
+}
+
+fn target_matches(key: &str, platform: &Platform) -> bool {
+    key.split('-').all(|part| {
+        part == platform.arch
+            || part == platform.os
+            || part == platform.libc
+            || (part == "glibc" && platform.libc == "gnu")
+    })
+}
+
+fn distro_matches(key: &str, platform: &Platform) -> bool {
+    platform.distro.is_some_and(|d| key == d.name || key == d.manager)
+}
+
+pub fn man_section(page: &str) -> Option<String> {
+    let name = Path::new(page).file_name()?.to_str()?;
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    let (_, ext) = name.rsplit_once('.')?;
+    ext.starts_with(|c: char| c.is_ascii_digit())
+        .then(|| ext[..1].to_string())
+}
+
+fn check_version(value: &Value) -> Result<(), Vec<ManifestError>> {
+    let current = env!("CARGO_PKG_VERSION");
+    if let Some(required) = value["radon_version"].as_str()
+        && utils::compare_versions(required, current) == Ordering::Greater {
+        return Err(vec![error("radon_version", format!(
+            "this manifest requires radon {} or newer, but this is radon {}; please upgrade radon",
+            required, current
+        ))]);
+    }
+    if let Some(schema) = value["schema"].as_u64()
+        && schema > SCHEMA_VERSION {
+        return Err(vec![error("schema", format!(
+            "this manifest uses schema {}, but radon {} only understands up to schema {}; please upgrade radon",
+            schema, current, SCHEMA_VERSION
+        ))]);
+    }
+    Ok(())
+}
+
+pub fn validate(value: &Value) -> Result<Manifest, Vec<ManifestError>> {
+    check_version(value)?;
+
+    let mut errors = Vec::new();
+    check_schema(&schema(), value, "", &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let manifest: Manifest = serde_json::from_value(value.clone())
+        .map_err(|e| vec![error("", e.to_string())])?;
+
+    check_semantics(&manifest, &mut errors);
+    if errors.is_empty() { Ok(manifest) } else { Err(errors) }
+}
+
+fn cargo_metadata(content: &str) -> Result<Option<Value>, Vec<ManifestError>> {
+    let cargo: toml::Value = toml::from_str(content)
+        .map_err(|e| vec![error("", format!("invalid TOML: {}", e))])?;
+    let radon = match cargo.get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("radon")) {
+        Some(radon) => radon,
+        None => return Ok(None),
+    };
+
+    let mut value = serde_json::to_value(radon)
+        .map_err(|e| vec![error("package.metadata.radon", e.to_string())])?;
+    if let Value::Object(map) = &mut value {
+        map.entry("build_system").or_insert_with(|| Value::String("cargo".to_string()));
+    }
+    Ok(Some(value))
+}
+
+pub fn cargo_has_metadata(content: &str) -> bool {
+    matches!(cargo_metadata(content), Ok(Some(_)))
+}
+
+pub fn parse_value(content: &str, file_name: &str) -> Result<Value, Vec<ManifestError>> {
+    if file_name == "Cargo.toml" {
+        cargo_metadata(content)?
+            .ok_or_else(|| vec![error("", "Cargo.toml has no [package.metadata.radon] table")])
+    } else if file_name.ends_with(".toml") {
+        let value: toml::Value = toml::from_str(content)
+            .map_err(|e| vec![error("", format!("invalid TOML: {}", e))])?;
+        serde_json::to_value(value).map_err(|e| vec![error("", e.to_string())])
+    } else if file_name.ends_with(".yaml") || file_name.ends_with(".yml") {
+        let value: serde_yaml::Value = serde_yaml::from_str(content)
+            .map_err(|e| vec![error("", format!("invalid YAML: {}", e))])?;
+        serde_json::to_value(value).map_err(|e| vec![error("", e.to_string())])
+    } else {
+        serde_json::from_str(content)
+            .map_err(|e| vec![error("", format!("invalid JSON: {}", e))])
+    }
+}
+
+pub fn parse(content: &str, file_name: &str) -> Result<Manifest, Vec<ManifestError>> {
+    let value = parse_value(content, file_name)?;
+    if file_name == "Cargo.toml" {
+        return validate(&value).map_err(|errors| {
+            errors.into_iter()
+                .map(|e| {
+                    let path = if e.path.is_empty() || e.path.starts_with('[') {
+                        format!("package.metadata.radon{}", e.path)
+                    } else {
+                        format!("package.metadata.radon.{}", e.path)
+                    };
+                    error(&path, e.message)
+                })
+                .collect()
+        });
+    }
+    validate(&value)
+}
+
+pub fn load(path: &Path) -> Result<Manifest, Vec<ManifestError>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| vec![error("", format!("failed to read {}: {}", path.display(), e))])?;
+    let file_name = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    parse(&content, &file_name)
+}
+
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    if let Some(file) = MANIFEST_FILES.iter().map(|f| dir.join(f)).find(|p| p.exists()) {
+        return Some(file);
+    }
+    let cargo_toml = dir.join("Cargo.toml");
+    fs::read_to_string(&cargo_toml)
+        .ok()
+        .filter(|content| cargo_has_metadata(content))
+        .map(|_| cargo_toml)
+}
+
+pub fn migrate(value: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    let Value::Object(map) = value else {
+        return changes;
+    };
+
+    let schema = map.get("schema").and_then(|s| s.as_u64()).unwrap_or(1);
+    if schema >= SCHEMA_VERSION {
+        return changes;
+    }
+
+    if schema < 2 && !map.contains_key("build_system") {
+        map.insert("build_system".to_string(), Value::String(default_build_system()));
+        changes.push(format!("made the implicit build_system \"{}\" explicit", default_build_system()));
+    }
+
+    map.insert("schema".to_string(), Value::from(SCHEMA_VERSION));
+    changes.push(format!("set schema from {} to {}", schema, SCHEMA_VERSION));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_options(build_system: &str) -> Manifest {
+        let content = format!(r#"{{
+            "build_system": "{}",
+            "binaries": {{ "bin/app": "app" }},
+            "options": {{
+                "gui": {{ "default": true }},
+                "ssl": {{ "flag": "WITH_SSL" }}
+            }}
+        }}"#, build_system);
+        parse(&content, "radon.json").unwrap_or_else(|errors| panic!("{}: {}", build_system, errors[0]))
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn option_flags_per_build_system() {
+        let enabled = strings(&["ssl"]);
+        assert_eq!(with_options("cmake").option_flags("cmake", &enabled, &[]), ["-Dgui=OFF", "-DWITH_SSL=ON"]);
+        assert_eq!(with_options("meson").option_flags("meson", &enabled, &[]), ["-Dgui=false", "-DWITH_SSL=true"]);
+        assert_eq!(with_options("autotools").option_flags("autotools", &enabled, &[]), ["--disable-gui", "--enable-WITH_SSL"]);
+        assert_eq!(with_options("make").option_flags("make", &enabled, &[]), ["gui=0", "WITH_SSL=1"]);
+    }
+
+    #[test]
+    fn cargo_options_are_features() {
+        let manifest = with_options("cargo");
+        assert_eq!(manifest.option_flags("cargo", &strings(&["gui", "ssl"]), &[]), ["--features", "gui,WITH_SSL"]);
+        assert!(manifest.option_flags("cargo", &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn disabling_a_cargo_default_feature_keeps_the_other_defaults() {
+        let manifest = with_options("cargo");
+        let defaults = strings(&["gui", "std"]);
+        assert_eq!(manifest.option_flags("cargo", &strings(&["gui"]), &defaults), ["--features", "gui"]);
+        assert_eq!(
+            manifest.option_flags("cargo", &strings(&["ssl"]), &defaults),
+            ["--no-default-features", "--features", "WITH_SSL,std"],
+        );
+    }
+
+    #[test]
+    fn options_need_a_build_system_that_can_take_them() {
+        let errors = match parse(r#"{ "build_system": "stack", "options": { "gui": {} } }"#, "radon.json") {
+            Ok(_) => panic!("options on stack should be rejected"),
+            Err(errors) => errors,
+        };
+        assert!(errors.iter().any(|e| e.path == "options"));
+    }
+
+    #[test]
+    fn resolve_options_applies_defaults_and_overrides() {
+        let manifest = with_options("cmake");
+        assert_eq!(manifest.resolve_options(&[], &[]).unwrap(), ["gui"]);
+        assert_eq!(manifest.resolve_options(&strings(&["ssl"]), &strings(&["gui"])).unwrap(), ["ssl"]);
+        assert!(manifest.resolve_options(&strings(&["nope"]), &[]).is_err());
+        assert!(manifest.resolve_options(&strings(&["gui"]), &strings(&["gui"])).is_err());
+    }
+
+    #[test]
+    fn target_keys_must_name_known_platform_parts() {
+        let with_target = |key: &str| format!(r#"{{
+            "build_system": "make",
+            "binaries": {{ "bin/app": "app" }},
+            "target": {{ "{}": {{ "dependencies": ["cmd:cc"] }} }}
+        }}"#, key);
+        assert!(parse(&with_target("x86_64-linux-musl"), "radon.json").is_ok());
+        assert!(parse(&with_target("aarch64"), "radon.json").is_ok());
+        for key in ["x86-64-musl", "x86_64-unknown-linux-gnu", "linux-"] {
+            let errors = parse(&with_target(key), "radon.json").err().unwrap_or_default();
+            assert_eq!(errors.len(), 1, "{}", key);
+            assert_eq!(errors[0].path, format!("target[{:?}]", key));
+        }
+    }
+
+    fn errors_for(value: Value) -> Vec<(String, String)> {
+        validate(&value).err().unwrap_or_default()
+            .into_iter()
+            .map(|e| (e.path, e.message))
+            .collect()
+    }
+
+    #[test]
+    fn schema_errors_point_at_the_offending_field() {
+        let errors = errors_for(json!({
+            "build_system": "scons",
+            "flags": "-O2",
+            "colour": "blue",
+            "build": [{ "dir": "src" }],
+            "target": { "linux": { "flag": [] } }
+        }));
+        let paths: Vec<&str> = errors.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["build[0].run", "build_system", "colour", "flags", "target.linux.flag"]);
+        assert_eq!(errors[0].1, "missing required field");
+        assert!(errors[1].1.starts_with("must be one of: make"));
+        assert_eq!(errors[2].1, "unknown field");
+        assert_eq!(errors[3].1, "expected array, found string");
+    }
+
+    #[test]
+    fn keys_that_are_not_identifiers_are_quoted_in_paths() {
+        let errors = errors_for(json!({ "binaries": { "bin/app": 1 } }));
+        assert_eq!(errors, [("binaries[\"bin/app\"]".to_string(), "expected string, found integer".to_string())]);
+    }
+
+    #[test]
+    fn semantic_errors_follow_a_valid_schema() {
+        let errors = errors_for(json!({ "build_system": "custom", "install": [{ "from": "../x", "to": "bin/x" }] }));
+        assert_eq!(errors, [("install[0].from".to_string(), "must be a relative path inside the repository".to_string())]);
+        assert!(errors_for(json!({ "binaries": { "bin/app": "app" } })).is_empty());
+    }
+
+    #[test]
+    fn migrate_makes_the_schema_1_build_system_explicit() {
+        let mut value = json!({ "binaries": { "bin/app": "app" } });
+        assert_eq!(migrate(&mut value), [
+            "made the implicit build_system \"make\" explicit",
+            "set schema from 1 to 2",
+        ]);
+        assert_eq!(value, json!({ "schema": 2, "build_system": "make", "binaries": { "bin/app": "app" } }));
+        assert!(validate(&value).is_ok());
+    }
+
+    #[test]
+    fn migrate_keeps_an_explicit_build_system_and_current_manifests() {
+        let mut value = json!({ "schema": 1, "build_system": "cmake" });
+        assert_eq!(migrate(&mut value), ["set schema from 1 to 2"]);
+        assert_eq!(value["build_system"], "cmake");
+        assert!(migrate(&mut value).is_empty());
+    }
+
+    #[test]
+    fn newer_schemas_ask_for_an_upgrade() {
+        let errors = errors_for(json!({ "schema": SCHEMA_VERSION + 1 }));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "schema");
+        assert!(errors[0].1.ends_with("please upgrade radon"));
+    }
+
+    #[test]
+    fn toml_and_yaml_manifests_match_json() {
+        let json = parse(r#"{ "build_system": "cmake", "flags": ["-DX=1"], "binaries": { "bin/app": "app" } }"#, "radon.json");
+        let toml = parse("build_system = \"cmake\"\nflags = [\"-DX=1\"]\n[binaries]\n\"bin/app\" = \"app\"\n", "radon.toml");
+        let yaml = parse("build_system: cmake\nflags: [\"-DX=1\"]\nbinaries:\n  bin/app: app\n", "radon.yml");
+        for manifest in [json, toml, yaml] {
+            let manifest = manifest.unwrap_or_else(|errors| panic!("{}", errors[0]));
+            assert_eq!(manifest.build_system, "cmake");
+            assert_eq!(manifest.flags, ["-DX=1"]);
+        }
+    }
+
+    #[test]
+    fn syntax_errors_name_the_format() {
+        let message = |content: &str, file_name: &str| parse(content, file_name).err().unwrap_or_default()[0].message.clone();
+        assert!(message("build_system = ", "radon.toml").starts_with("invalid TOML"));
+        assert!(message("build_system: [", "radon.yaml").starts_with("invalid YAML"));
+        assert!(message("{", "radon.json").starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn cargo_metadata_implies_the_cargo_build_system() {
+        let content = "[package]\nname = \"app\"\n\n[package.metadata.radon]\ndependencies = [\"cmd:git\"]\n";
+        assert!(cargo_has_metadata(content));
+        let manifest = parse(content, "Cargo.toml").unwrap_or_else(|errors| panic!("{}", errors[0]));
+        assert_eq!(manifest.build_system, "cargo");
+        assert_eq!(manifest.dependencies, ["cmd:git"]);
+
+        let explicit = "[package.metadata.radon]\nbuild_system = \"make\"\nbinaries = { \"bin/app\" = \"app\" }\n";
+        assert_eq!(parse(explicit, "Cargo.toml").map(|m| m.build_system).unwrap_or_default(), "make");
+    }
+
+    #[test]
+    fn cargo_metadata_errors_are_prefixed() {
+        assert!(!cargo_has_metadata("[package]\nname = \"app\"\n"));
+        let paths = |content: &str| -> Vec<String> {
+            parse(content, "Cargo.toml").err().unwrap_or_default().into_iter().map(|e| e.path).collect()
+        };
+        assert_eq!(paths("[package]\nname = \"app\"\n"), [""]);
+        assert_eq!(paths("[package.metadata.radon]\ncolour = \"blue\"\n"), ["package.metadata.radon.colour"]);
+        assert_eq!(paths("[package.metadata.radon.binaries]\n\"bin/app\" = 1\n"), ["package.metadata.radon.binaries[\"bin/app\"]"]);
+    }
+
+    #[test]
+    fn find_prefers_radon_files_over_cargo_metadata() {
+        let dir = std::env::temp_dir().join(format!("radon-manifest-find-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        assert_eq!(find(&dir), None);
+        fs::write(dir.join("Cargo.toml"), "[package.metadata.radon]\n").unwrap();
+        assert_eq!(find(&dir), Some(dir.join("Cargo.toml")));
+        fs::write(dir.join("radon.yaml"), "build_system: make\n").unwrap();
+        assert_eq!(find(&dir), Some(dir.join("radon.yaml")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use crate::manifest;
+use crate::utils;
+
+#[derive(Clone, PartialEq)]
+pub struct PackageRef {
+    pub forge: String,
+    pub repo: String,
+    pub git_ref: Option<String>,
+}
+
+pub struct ResolvedPackage {
+    pub package: PackageRef,
+    pub depends: Vec<String>,
+    pub as_dependency: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+    Visiting,
+    Done,
+}
+
+impl PackageRef {
+    pub fn parse(spec: &str) -> Option<PackageRef> {
+        let (forge, rest) = spec.split_once(':')?;
+        utils::forge_domain(forge)?;
+
+        let (repo, git_ref) = match rest.split_once('@') {
+            Some((repo, git_ref)) if !git_ref.is_empty() => (repo, Some(git_ref.to_string())),
+            Some(_) => return None,
+            None => (rest, None),
+        };
+
+        let segments: Vec<&str> = repo.split('/').collect();
+        if segments.len() < 2 || segments.iter().any(|s| s.is_empty()) {
+            return None;
+        }
+
+        Some(PackageRef {
+            forge: forge.to_string(),
+            repo: repo.to_string(),
+            git_ref,
+        })
+    }
+
+    pub fn is_package_spec(spec: &str) -> bool {
+        spec.split_once(':')
+            .is_some_and(|(forge, _)| utils::forge_domain(forge).is_some())
+    }
+
+    pub fn name(&self) -> &str {
+        self.repo.rsplit('/').next().unwrap()
+    }
+
+    pub fn url(&self) -> String {
+        format!("https://{}/{}", utils::forge_domain(&self.forge).unwrap(), self.repo)
+    }
+}
+
+impl fmt::Display for PackageRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.forge, self.repo)?;
+        if let Some(git_ref) = &self.git_ref {
+            write!(f, "@{}", git_ref)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn resolve(roots: &[PackageRef]) -> Result<Vec<ResolvedPackage>, String> {
+    let installed: Vec<String> = utils::get_installed_packages()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    resolve_with(roots, &installed, &fetch_package_deps)
+}
+
+fn resolve_with(
+    roots: &[PackageRef],
+    installed: &[String],
+    fetch: &dyn Fn(&PackageRef) -> Result<Vec<PackageRef>, String>,
+) -> Result<Vec<ResolvedPackage>, String> {
+    let mut states = HashMap::new();
+    let mut stack = Vec::new();
+    let mut order = Vec::new();
+
+    for root in roots {
+        visit(root, false, installed, fetch, &mut states, &mut stack, &mut order)?;
+    }
+    Ok(order)
+}
+
+fn visit(
+    package: &PackageRef,
+    as_dependency: bool,
+    installed: &[String],
+    fetch: &dyn Fn(&PackageRef) -> Result<Vec<PackageRef>, String>,
+    states: &mut HashMap<String, VisitState>,
+    stack: &mut Vec<String>,
+    order: &mut Vec<ResolvedPackage>,
+) -> Result<(), String> {
+    let name = package.name().to_string();
+
+    match states.get(&name) {
+        Some(VisitState::Done) => {
+            if !as_dependency && let Some(entry) = order.iter_mut().find(|e| e.package.name() == name) {
+                entry.as_dependency = false;
+            }
+            return Ok(());
+        }
+        Some(VisitState::Visiting) => {
+            let start = stack.iter().position(|n| *n == name).unwrap_or(0);
+            let mut cycle: Vec<&str> = stack[start..].iter().map(|s| s.as_str()).collect();
+            cycle.push(&name);
+            return Err(format!("Dependency cycle detected: {}", cycle.join(" -> ")));
+        }
+        None => {}
+    }
+
+    if as_dependency && installed.contains(&name) {
+        states.insert(name, VisitState::Done);
+        return Ok(());
+    }
+
+    states.insert(name.clone(), VisitState::Visiting);
+    stack.push(name.clone());
+
+    let deps = fetch(package)?;
+    for dep in &deps {
+        visit(dep, true, installed, fetch, states, stack, order)?;
+    }
+
+    stack.pop();
+    states.insert(name, VisitState::Done);
+    order.push(ResolvedPackage {
+        package: package.clone(),
+        depends: deps.iter().map(|d| d.name().to_string()).collect(),
+        as_dependency,
+    });
+    Ok(())
+}
+
+fn fetch_package_deps(package: &PackageRef) -> Result<Vec<PackageRef>, String> {
+    let dir = Path::new("/tmp/radon/resolve").join(package.name());
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("Failed to clean {}: {}", dir.display(), e))?;
+    }
+
+    let mut clone = Command::new("git");
+    clone
+        .arg("clone")
+        .arg("--depth=1")
+        .arg("--no-checkout")
+        .arg("--filter=blob:none");
+    if let Some(git_ref) = &package.git_ref {
+        clone.arg("--branch").arg(git_ref);
+    }
+    let status = clone
+        .arg(package.url())
+        .arg(&dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Git command failed: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("Failed to fetch {}", package));
+    }
+
+    let file_names = manifest::MANIFEST_FILES.iter().copied().chain(["Cargo.toml"]);
+    for file_name in file_names {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&dir)
+            .arg("show")
+            .arg(format!("HEAD:{}", file_name))
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| format!("Git command failed: {}", e))?;
+
+        if !output.status.success() {
+            continue;
+        }
+
+        let content = String::from_utf8_lossy(&output.stdout);
+        if file_name == "Cargo.toml" && !manifest::cargo_has_metadata(&content) {
+            break;
+        }
+
+        let mut manifest = manifest::parse(&content, file_name).map_err(|errors| {
+            let details: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            format!("Invalid {} in {}: {}", file_name, package, details.join("; "))
+        })?;
+        manifest.apply_platform(&utils::detect_platform());
+        return Ok(manifest.package_dependencies());
+    }
+
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(spec: &str) -> PackageRef {
+        PackageRef::parse(spec).unwrap_or_else(|| panic!("{}", spec))
+    }
+
+    // Resolves against a fixed graph of "name -> dependency specs"
+    fn resolve_graph(roots: &[&str], graph: &[(&str, &[&str])], installed: &[&str]) -> Result<Vec<ResolvedPackage>, String> {
+        let roots: Vec<PackageRef> = roots.iter().map(|r| package(r)).collect();
+        let installed: Vec<String> = installed.iter().map(|i| i.to_string()).collect();
+        let fetch = |pkg: &PackageRef| -> Result<Vec<PackageRef>, String> {
+            let (_, deps) = graph.iter().find(|(name, _)| *name == pkg.name()).ok_or(format!("Failed to fetch {}", pkg))?;
+            Ok(deps.iter().map(|d| package(d)).collect())
+        };
+        resolve_with(&roots, &installed, &fetch)
+    }
+
+    #[test]
+    fn package_refs_parse_forge_repo_and_ref() {
+        let pkg = package("github:owner/tool@v1.2");
+        assert_eq!(pkg.to_string(), "github:owner/tool@v1.2");
+        assert_eq!(pkg.name(), "tool");
+        assert_eq!(pkg.url(), "https://github.com/owner/tool");
+        assert!(package("gitlab:group/sub/tool").git_ref.is_none());
+        assert_eq!(package("gitlab:group/sub/tool").name(), "tool");
+    }
+
+    #[test]
+    fn invalid_package_refs_are_rejected() {
+        for spec in ["github:tool", "github:owner/", "github:/tool", "github:owner/tool@", "svn:owner/tool", "owner/tool"] {
+            assert!(PackageRef::parse(spec).is_none(), "{}", spec);
+        }
+        assert!(PackageRef::is_package_spec("codeberg:owner/tool"));
+        assert!(!PackageRef::is_package_spec("pkgconfig:openssl"));
+    }
+
+    #[test]
+    fn dependencies_come_before_their_dependents() {
+        let graph: &[(&str, &[&str])] = &[
+            ("app", &["github:o/lib", "github:o/util"]),
+            ("lib", &["github:o/util"]),
+            ("util", &[]),
+        ];
+        let order = resolve_graph(&["github:o/app"], graph, &[]).unwrap_or_else(|e| panic!("{}", e));
+        let names: Vec<&str> = order.iter().map(|r| r.package.name()).collect();
+        assert_eq!(names, ["util", "lib", "app"]);
+        assert_eq!(order[2].depends, ["lib", "util"]);
+        assert!(order[0].as_dependency && order[1].as_dependency && !order[2].as_dependency);
+    }
+
+    #[test]
+    fn installed_dependencies_are_not_fetched_again() {
+        let graph: &[(&str, &[&str])] = &[("app", &["github:o/lib"])];
+        let order = resolve_graph(&["github:o/app"], graph, &["lib"]).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(order.len(), 1);
+    }
+
+    #[test]
+    fn dependency_cycles_are_reported() {
+        let graph: &[(&str, &[&str])] = &[("a", &["github:o/b"]), ("b", &["github:o/a"])];
+        let err = resolve_graph(&["github:o/a"], graph, &[]).err().unwrap_or_default();
+        assert_eq!(err, "Dependency cycle detected: a -> b -> a");
+    }
+}
@@ -1,11 +1,17 @@
-use std::fs;
-use std::fs::File;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
 use ansi_term::Colour::Red;
 use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use crate::deps::{self, Requirement};
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct InstalledPackage {
     pub name: String,
     pub source: Option<String>,
@@ -14,62 +20,354 @@ pub struct InstalledPackage {
     pub build_file: Option<String>,
     pub hash: Option<String>,
     pub version: Option<String>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub as_dependency: bool,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    #[serde(default)]
+    pub required_by: Vec<String>,
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    #[serde(default)]
+    pub repo_url: Option<String>,
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+    #[serde(default)]
+    pub patches: Vec<String>,
+    #[serde(default)]
+    pub installed: Option<String>,
+    #[serde(default)]
+    pub with: Vec<String>,
+    #[serde(default)]
+    pub without: Vec<String>,
+    #[serde(default)]
+    pub pre_remove: Vec<String>,
+    #[serde(default)]
+    pub post_remove: Vec<String>,
+    #[serde(default)]
+    pub file_info: BTreeMap<String, InstalledFile>,
 }
 
-pub fn check_deps(deps: &[String]) {
-    let missing: Vec<&str> = deps
-        .iter()
-        .filter(|d| {
-            !Command::new("sh")
-                .arg("-c")
-                .arg(format!("command -v {}", d))
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-                .map(|s| s.success())
-                .unwrap_or(false)
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct InstalledFile {
+    pub sha256: String,
+    pub mode: String,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl InstalledFile {
+    pub fn read(path: &Path) -> Option<InstalledFile> {
+        let metadata = fs::metadata(path).ok()?;
+        let content = fs::read(path).ok()?;
+        Some(InstalledFile {
+            sha256: format!("{:x}", Sha256::digest(&content)),
+            mode: format!("{:o}", metadata.mode() & 0o7777),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
         })
-        .map(|d| d.as_str())
-        .collect();
+    }
+}
 
-    if !missing.is_empty() {
-        eprintln!("{}", Red.paint("MISSING DEPENDENCIES:"));
-        for d in &missing {
-            println!("- {}", d);
+impl InstalledPackage {
+    pub fn installed_files(&self) -> Vec<String> {
+        if self.files.is_empty() {
+            vec![self.location.clone()]
+        } else {
+            self.files.clone()
         }
-        let pm = detect_package_manager();
-        println!("\nRUN:\nsudo {} {}", pm, missing.join(" "));
-        std::process::exit(1);
     }
 }
 
-pub fn detect_package_manager() -> &'static str {
-    if Path::new("/etc/apt/sources.list").exists() {
-        "apt install"
-    } else if Path::new("/etc/pacman.conf").exists() {
-        "pacman -S"
-    } else if Path::new("/etc/xbps.d").exists() {
-        "xbps-install"
-    } else if Path::new("/etc/dnf/dnf.conf").exists() {
-        "dnf install"
-    } else if Path::new("/etc/zypp/zypp.conf").exists() {
-        "zypper install"
+pub fn forge_domain(forge: &str) -> Option<&'static str> {
+    match forge {
+        "github" => Some("github.com"),
+        "gitlab" => Some("gitlab.com"),
+        "codeberg" => Some("codeberg.org"),
+        _ => None,
+    }
+}
+
+pub fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
     } else {
-        "your-package-manager install"
+        format!("{:.1} {}", size, units[unit])
     }
 }
 
-pub fn get_privilege_command() -> String {
-    let doas_available = Command::new("sh")
-        .arg("-c")
-        .arg("command -v doas")
-        .stdout(Stdio::null())
+pub fn ls_remote(url: &str, options: &[&str], patterns: &[&str]) -> Option<Vec<(String, String)>> {
+    let output = Command::new("git")
+        .arg("ls-remote")
+        .args(options)
+        .arg(url)
+        .args(patterns)
         .stderr(Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false);
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let refs = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(sha, name)| (sha.to_string(), name.to_string()))
+        .collect();
+    Some(refs)
+}
+
+pub fn remote_commit(pkg: &InstalledPackage) -> Option<String> {
+    let url = pkg.repo_url.as_deref()?;
+    let Some(branch) = pkg.branch.as_deref() else {
+        return ls_remote(url, &[], &["HEAD"])?.into_iter().next().map(|(sha, _)| sha);
+    };
+    let peeled = format!("{}^{{}}", branch);
+    let refs = ls_remote(url, &[], &[branch, &peeled])?;
+
+    // Patterns match any ref ending in the name, so look for exact refs.
+    // A peeled annotated tag (^{}) points at the commit itself.
+    [format!("refs/heads/{}", branch), format!("refs/tags/{}^{{}}", branch), format!("refs/tags/{}", branch)]
+        .iter()
+        .find_map(|wanted| refs.iter().find(|(_, name)| name == wanted))
+        .map(|(sha, _)| sha.clone())
+}
+
+// Splits "v1.2.3-rc.1+build" into numeric parts and a pre-release label.
+// Only a '-' followed by a letter starts a pre-release, "1.2-3" stays numeric.
+fn split_version(v: &str) -> (Vec<u64>, Option<&str>) {
+    let v = v.trim_start_matches('v');
+    let v = v.split('+').next().unwrap_or(v);
+    let (core, pre) = match v.find('-').filter(|&i| v[i + 1..].starts_with(|c: char| c.is_ascii_alphabetic())) {
+        Some(i) => (&v[..i], Some(&v[i + 1..])),
+        None => (v, None),
+    };
+    let parts = core.split(['.', '-'])
+        .map(|p| p.chars().take_while(|c| c.is_ascii_digit()).collect::<String>())
+        .map(|p| p.parse().unwrap_or(0))
+        .collect();
+    (parts, pre)
+}
+
+pub fn is_prerelease(v: &str) -> bool {
+    split_version(v).1.is_some()
+}
+
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let ((a, a_pre), (b, b_pre)) = (split_version(a), split_version(b));
+    for i in 0..a.len().max(b.len()) {
+        let ord = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    // A release ranks above its pre-releases: 1.0.0-rc.2 < 1.0.0
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_prerelease(a, b),
+    }
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.split('.'), b.split('.'));
+    loop {
+        let ord = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+fn find_missing_deps(deps: &[String]) -> Vec<(String, String, Option<Requirement>)> {
+    deps.iter()
+        .filter_map(|dep| match Requirement::parse(dep) {
+            Ok(req) => req.check()
+                .err()
+                .map(|reason| (req.to_string(), reason, Some(req))),
+            Err(reason) => Some((dep.clone(), reason, None)),
+        })
+        .collect()
+}
+
+pub fn check_deps(deps: &[String], yes: bool) -> bool {
+    let missing = find_missing_deps(deps);
+    if missing.is_empty() {
+        return true;
+    }
+
+    eprintln!("{}", Red.paint("MISSING DEPENDENCIES:"));
+    for (requirement, reason, _) in &missing {
+        println!("- {} ({})", requirement, reason);
+    }
+
+    let distro = detect_distro();
+    let mut packages: Vec<String> = Vec::new();
+    let mut manual: Vec<&str> = Vec::new();
+    for (requirement, _, req) in &missing {
+        match req.as_ref().and_then(|req| deps::distro_packages(req, distro)) {
+            Some(names) => {
+                for package in names {
+                    if !packages.contains(&package) {
+                        packages.push(package);
+                    }
+                }
+            }
+            None => manual.push(requirement),
+        }
+    }
+    if !manual.is_empty() {
+        println!("\n~> No known package for these, resolve them manually: {}", manual.join(", "));
+    }
+
+    let privilege_cmd = get_privilege_command();
+    if packages.is_empty() {
+        return false;
+    }
+    let Some(distro) = distro else {
+        println!("\nRUN:\n{} {} {}", privilege_cmd, detect_package_manager(), packages.join(" "));
+        return false;
+    };
+
+    if !yes {
+        print!("~> Install them now with `{} {} {}`? [Y/n] ", privilege_cmd, distro.install, packages.join(" "));
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+        if input.trim().eq_ignore_ascii_case("n") {
+            println!("\nRUN:\n{} {} {}", privilege_cmd, distro.install, packages.join(" "));
+            return false;
+        }
+    }
+
+    let install_cmd = if yes { distro.install_yes } else { distro.install };
+    let status = Command::new(&privilege_cmd)
+        .args(install_cmd.split_whitespace())
+        .args(&packages)
+        .status();
+    if !status.map(|s| s.success()).unwrap_or(false) {
+        eprintln!("{}: Failed to install system dependencies", Red.paint("Error"));
+        return false;
+    }
+
+    let still_missing = find_missing_deps(deps);
+    if !still_missing.is_empty() {
+        eprintln!("{}", Red.paint("STILL MISSING AFTER INSTALL:"));
+        for (requirement, reason, _) in &still_missing {
+            println!("- {} ({})", requirement, reason);
+        }
+        return false;
+    }
+    true
+}
 
-    if doas_available {
+pub struct Distro {
+    pub marker: &'static str,
+    pub name: &'static str,
+    pub manager: &'static str,
+    pub install: &'static str,
+    pub install_yes: &'static str,
+}
+
+pub const DISTROS: &[Distro] = &[
+    Distro { marker: "/etc/apt/sources.list", name: "debian", manager: "apt", install: "apt install", install_yes: "apt install -y" },
+    Distro { marker: "/etc/pacman.conf", name: "arch", manager: "pacman", install: "pacman -S", install_yes: "pacman -S --noconfirm" },
+    Distro { marker: "/etc/xbps.d", name: "void", manager: "xbps", install: "xbps-install", install_yes: "xbps-install -y" },
+    Distro { marker: "/etc/dnf/dnf.conf", name: "fedora", manager: "dnf", install: "dnf install", install_yes: "dnf install -y" },
+    Distro { marker: "/etc/zypp/zypp.conf", name: "opensuse", manager: "zypper", install: "zypper install", install_yes: "zypper --non-interactive install" },
+    Distro { marker: "/etc/alpine-release", name: "alpine", manager: "apk", install: "apk add", install_yes: "apk add" },
+    Distro { marker: "/etc/gentoo-release", name: "gentoo", manager: "emerge", install: "emerge", install_yes: "emerge" },
+    Distro { marker: "/bin/freebsd-version", name: "freebsd", manager: "pkg", install: "pkg install", install_yes: "pkg install -y" },
+];
+
+pub struct Platform {
+    pub arch: String,
+    pub os: String,
+    pub libc: String,
+    pub distro: Option<&'static Distro>,
+}
+
+pub fn detect_distro() -> Option<&'static Distro> {
+    DISTROS.iter().find(|d| Path::new(d.marker).exists())
+}
+
+pub fn detect_package_manager() -> &'static str {
+    detect_distro()
+        .map(|d| d.install)
+        .unwrap_or("your-package-manager install")
+}
+
+pub fn detect_libc() -> &'static str {
+    let has_musl_loader = ["/lib", "/usr/lib"].iter().any(|dir| {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .any(|e| e.file_name().to_string_lossy().starts_with("ld-musl-"))
+            })
+            .unwrap_or(false)
+    });
+    if has_musl_loader { "musl" } else { "gnu" }
+}
+
+pub fn detect_platform() -> Platform {
+    Platform {
+        arch: std::env::consts::ARCH.to_string(),
+        os: std::env::consts::OS.to_string(),
+        libc: detect_libc().to_string(),
+        distro: detect_distro(),
+    }
+}
+
+pub fn get_privilege_command() -> String {
+    if deps::command_exists("doas") {
         "doas".into()
     } else {
         "sudo".into()
@@ -82,27 +380,21 @@ pub fn setup_radon_dirs() {
     let buildfiles = var_lib_radon.join("buildfiles");
     
     if !etc_radon.exists() {
-        let status = Command::new(&get_privilege_command())
+        let status = Command::new(get_privilege_command())
             .arg("mkdir")
             .arg("-p")
             .arg(etc_radon)
             .status();
         if status.is_ok() && status.unwrap().success() {
-            let _ = Command::new(&get_privilege_command())
+            let _ = Command::new(get_privilege_command())
                 .arg("touch")
                 .arg(etc_radon.join("installed.yaml"))
                 .status();
         }
     }
 
-    if !var_lib_radon.exists() {
-        let _ = Command::new(&get_privilege_command())
-            .arg("mkdir")
-            .arg("-p")
-            .arg(&buildfiles)
-            .status();
-    } else if !buildfiles.exists() {
-        let _ = Command::new(&get_privilege_command())
+    if !buildfiles.exists() {
+        let _ = Command::new(get_privilege_command())
             .arg("mkdir")
             .arg("-p")
             .arg(&buildfiles)
@@ -114,8 +406,186 @@ pub fn get_installed_packages() -> Vec<InstalledPackage> {
     let path = Path::new("/etc/radon/installed.yaml");
     if path.exists() {
         let file = File::open(path).expect("Failed to open installed.yaml");
-        serde_yaml::from_reader(file).unwrap_or_else(|_| vec![])
+        let mut installed: Vec<InstalledPackage> = serde_yaml::from_reader(file).unwrap_or_else(|_| vec![]);
+        update_reverse_deps(&mut installed);
+        installed
     } else {
         Vec::new()
     }
 }
+
+pub fn save_installed_packages(installed: &[InstalledPackage]) {
+    let mut installed = installed.to_vec();
+    update_reverse_deps(&mut installed);
+
+    let temp_path = Path::new("/tmp").join("radon-installed.yaml");
+    fs::write(&temp_path, serde_yaml::to_string(&installed).unwrap()).unwrap();
+
+    Command::new(get_privilege_command())
+        .arg("mv")
+        .arg(&temp_path)
+        .arg("/etc/radon/installed.yaml")
+        .status()
+        .expect("Failed to update package list");
+}
+
+fn update_reverse_deps(installed: &mut [InstalledPackage]) {
+    let edges: Vec<(String, String)> = installed.iter()
+        .flat_map(|p| p.depends.iter().map(|d| (d.clone(), p.name.clone())))
+        .collect();
+
+    for pkg in installed.iter_mut() {
+        pkg.required_by = edges.iter()
+            .filter(|(dep, _)| *dep == pkg.name)
+            .map(|(_, dependent)| dependent.clone())
+            .collect();
+        pkg.required_by.sort();
+        pkg.required_by.dedup();
+    }
+}
+
+pub fn find_orphans(installed: &[InstalledPackage]) -> Vec<String> {
+    let mut remaining: Vec<&InstalledPackage> = installed.iter().collect();
+    let mut orphans = Vec::new();
+
+    loop {
+        let found: Vec<String> = remaining.iter()
+            .filter(|p| p.as_dependency)
+            .filter(|p| !remaining.iter().any(|other| other.depends.contains(&p.name)))
+            .map(|p| p.name.clone())
+            .collect();
+        if found.is_empty() {
+            return orphans;
+        }
+        remaining.retain(|p| !found.contains(&p.name));
+        orphans.extend(found);
+    }
+}
+
+pub fn run_hook(
+    hook: &str,
+    commands: &[String],
+    dir: &Path,
+    prefix: &Path,
+    name: &str,
+    version: Option<&str>,
+    privileged: bool,
+) -> bool {
+    if commands.is_empty() {
+        return true;
+    }
+    println!("~> Running {} hook", hook);
+
+    let vars = [
+        ("PATH".to_string(), std::env::var("PATH").unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string())),
+        ("HOME".to_string(), std::env::var("HOME").unwrap_or_default()),
+        ("PREFIX".to_string(), prefix.to_string_lossy().to_string()),
+        ("PKG_NAME".to_string(), name.to_string()),
+        ("VERSION".to_string(), version.unwrap_or("").to_string()),
+    ];
+
+    for command in commands {
+        let mut cmd = if privileged {
+            let mut cmd = Command::new(get_privilege_command());
+            cmd.arg("env").arg("-i");
+            for (key, value) in &vars {
+                cmd.arg(format!("{}={}", key, value));
+            }
+            cmd.arg("sh");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.env_clear().envs(vars.iter().cloned());
+            cmd
+        };
+
+        let status = cmd
+            .arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .status();
+
+        if !status.map(|s| s.success()).unwrap_or(false) {
+            eprintln!("{}: {} hook failed: {}", Red.paint("Error"), hook, command);
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, as_dependency: bool, depends: &[&str]) -> InstalledPackage {
+        InstalledPackage {
+            name: name.to_string(),
+            as_dependency,
+            depends: depends.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert_eq!(compare_versions("1.10", "1.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2-3", "1.2-10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0+build.5", "1.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn prereleases_rank_below_the_release() {
+        assert_eq!(compare_versions("v1.0.0-rc.2", "v1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0-rc.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-rc.2", "1.0.0-rc.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-rc", "1.0.0-rc.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.1.0-alpha", "1.0.0"), Ordering::Greater);
+        assert!(is_prerelease("v2.0-beta.1"));
+        assert!(!is_prerelease("2.0-1"));
+    }
+
+    #[test]
+    fn reverse_deps_follow_depends() {
+        let mut installed = vec![
+            package("app", false, &["libfoo"]),
+            package("tool", false, &["libfoo"]),
+            package("libfoo", true, &[]),
+        ];
+        update_reverse_deps(&mut installed);
+        assert_eq!(installed[2].required_by, ["app", "tool"]);
+        assert!(installed[0].required_by.is_empty());
+    }
+
+    #[test]
+    fn needed_dependencies_are_not_orphans() {
+        let installed = vec![
+            package("app", false, &["libfoo"]),
+            package("libfoo", true, &["libbar"]),
+            package("libbar", true, &[]),
+        ];
+        assert!(find_orphans(&installed).is_empty());
+    }
+
+    #[test]
+    fn orphaned_dependency_chain_is_found() {
+        // "app" was removed, leaving its whole dependency chain behind
+        let installed = vec![
+            package("libfoo", true, &["libbar"]),
+            package("libbar", true, &[]),
+            package("tool", false, &[]),
+        ];
+        assert_eq!(find_orphans(&installed), ["libfoo", "libbar"]);
+    }
+
+    #[test]
+    fn explicit_packages_are_never_orphans() {
+        let installed = vec![
+            package("libfoo", false, &[]),
+            package("other", true, &["shared"]),
+            package("keeper", false, &["shared"]),
+            package("shared", true, &[]),
+        ];
+        assert_eq!(find_orphans(&installed), ["other"]);
+    }
+}